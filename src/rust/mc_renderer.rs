@@ -1,21 +1,84 @@
 use std::collections::HashMap;
 use std::io::{Write, stdout};
 use std::ops::Add;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::Duration;
 use std::{fs, thread};
 
 use arboard::{Clipboard, ImageData};
-use crossterm::event;
+use crossterm::cursor::{Hide, Show};
 use crossterm::event::{Event, KeyCode, KeyEventKind};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::terminal::{
+	EnterAlternateScreen,
+	LeaveAlternateScreen,
+	disable_raw_mode,
+	enable_raw_mode,
+	size,
+};
+use crossterm::{event, execute};
 use image::buffer::ConvertBuffer;
-use image::{GenericImage, Rgb, RgbImage, RgbaImage, open};
+use image::imageops::{self, FilterType};
+use image::{Rgb, RgbImage, RgbaImage, load_from_memory};
 use minifb::{Window, WindowOptions};
 use rusttype::{Font, Scale, point};
+use serde::Deserialize;
 
 use crate::main;
-use crate::stuff::{input, menu};
+use crate::stuff::{copy_text_to_clipboard, input, menu};
+
+pub mod align;
+pub mod component;
+pub mod formats;
+pub mod head;
+pub mod item;
+pub mod measure;
+pub mod palette;
+pub mod renderer;
+pub mod scene;
+pub mod server;
+pub mod snbt;
+pub mod svg;
+pub mod tab_list;
+pub mod terminal;
+pub mod translate;
+
+use align::{Align, split_alignments};
+use formats::{TextFormat, export, import};
+use measure::split_lines;
+use palette::{Edition, Palette};
+pub use renderer::Renderer;
+use svg::{SvgMode, render_svg};
+use terminal::{crop_to_text, fit, half_blocks};
+use translate::Translator;
+
+const LINE_HEIGHT: f32 = 1.15;
+
+/// Where the rendered text is placed and what is drawn behind it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+	/// Text over the whole background screenshot, like the interactive renderer.
+	#[default]
+	Screenshot,
+	/// Only the chat box, with vanilla's translucent backdrop and text shadow.
+	Chat,
+	/// An item tooltip, the first line being the item's name.
+	Tooltip,
+}
+
+impl FromStr for Layout {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			| "screenshot" => Ok(Layout::Screenshot),
+			| "chat" => Ok(Layout::Chat),
+			| "tooltip" => Ok(Layout::Tooltip),
+			| _ => Err(format!("Unknown layout '{}'", s)),
+		}
+	}
+}
 
 fn save(
 	image: &RgbImage,
@@ -24,7 +87,40 @@ fn save(
 ) {
 	match save_type {
 		| 1 => save_image_to_clipboard(image),
-		| 2 => save_image_to_file(image, path),
+		| 2 => save_to_output(path, |path| image.save(path)),
+		| _ => {},
+	}
+}
+
+/// Asks which edition's colors to use, and for a palette file of a resource pack that recolors
+/// them.
+fn prompt_palette() -> Palette {
+	println!("\nWhich edition's colors should be used?");
+	let options: Vec<&str> = Edition::ALL.iter().map(Edition::name).collect();
+	let edition = Edition::ALL[menu(options) as usize - 1];
+	let palette_path = input(
+		"\nPath to a palette file with recolored codes, like {\"colors\": {\"a\": \"#66ee66\"}} \
+		 (leave empty to skip):",
+		true,
+	);
+	if palette_path.is_empty() {
+		return Palette::for_edition(edition);
+	}
+	Palette::load(&palette_path, edition).unwrap_or_else(|e| {
+		println!("\x1b[31m{}\x1b[0m", e);
+		Palette::for_edition(edition)
+	})
+}
+
+/// Asks whether to copy `image` to the clipboard or save it as a PNG, then does it.
+fn prompt_save(image: &RgbImage) {
+	let save_type = menu(vec!["Save to clipboard", "Save as file"]);
+	match save_type {
+		| 1 => save(image, "", save_type),
+		| 2 => {
+			let path = input("\nEnter the filename to save the image as:", true);
+			save(image, &format!("{}.png", path), save_type);
+		},
 		| _ => {},
 	}
 }
@@ -54,15 +150,17 @@ fn save_image_to_clipboard(image: &RgbImage) {
 	}
 }
 
-fn save_image_to_file(
-	image: &RgbImage,
-	path: &str,
+fn clipboard_text() -> Option<String> { Clipboard::new().ok()?.get_text().ok() }
+
+/// Writes the file named `name` into the output folder with `write`, then prints where it went.
+fn save_to_output<E: std::fmt::Debug>(
+	name: &str,
+	write: impl FnOnce(&Path) -> Result<(), E>,
 ) {
 	fs::create_dir_all("output").expect("Failed to create output directory");
-	let full_path = format!("output\\{}", path);
-	image.save(&full_path).expect("Failed to save image");
-	let absolute_path =
-		fs::canonicalize(Path::new(&full_path)).expect("Failed to get absolute path of image");
+	let full_path = Path::new("output").join(name);
+	write(&full_path).expect("Failed to save file");
+	let absolute_path = fs::canonicalize(&full_path).expect("Failed to get absolute path of file");
 	let display_path = absolute_path
 		.strip_prefix(r"\\?\")
 		.unwrap_or(&absolute_path);
@@ -72,141 +170,308 @@ fn save_image_to_file(
 	);
 }
 
-fn render_text(
+/// `value` with at most two decimals and without trailing zeros.
+fn format_number(value: impl Into<f64>) -> String {
+	let formatted = format!("{:.2}", value.into());
+	formatted
+		.trim_end_matches('0')
+		.trim_end_matches('.')
+		.to_string()
+}
+
+/// Formatting applied to a character by the `&`/`§` codes before it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Style {
+	color:         Rgb<u8>,
+	bold:          bool,
+	italic:        bool,
+	strikethrough: bool,
+	underline:     bool,
+}
+
+impl Default for Style {
+	fn default() -> Self {
+		Style {
+			color:         Rgb([255, 255, 255]),
+			bold:          false,
+			italic:        false,
+			strikethrough: false,
+			underline:     false,
+		}
+	}
+}
+
+impl Style {
+	fn font_key(&self) -> &'static str {
+		match (self.bold, self.italic) {
+			| (true, true) => "bold_italic",
+			| (true, false) => "bold",
+			| (false, true) => "italic",
+			| (false, false) => "regular",
+		}
+	}
+}
+
+/// Byte index of the first `pattern` in `text` that doesn't start with an escaped character, so
+/// `\{head:Name}` stays text.
+fn find_unescaped(
 	text: &str,
-	fonts: &HashMap<&str, Font>,
-	image: &mut RgbImage,
-	scale: Scale,
-) -> RgbImage {
-	let colors: HashMap<char, Rgb<u8>> = [
-		('0', Rgb([0, 0, 0])),       // black
-		('1', Rgb([0, 0, 170])),     // dark blue
-		('2', Rgb([0, 170, 0])),     // dark green
-		('3', Rgb([0, 170, 170])),   // dark aqua
-		('4', Rgb([170, 0, 0])),     // dark red
-		('5', Rgb([170, 0, 170])),   // dark purple
-		('6', Rgb([255, 170, 0])),   // gold
-		('7', Rgb([170, 170, 170])), // gray
-		('8', Rgb([85, 85, 85])),    // dark gray
-		('9', Rgb([85, 85, 255])),   // blue
-		('a', Rgb([85, 255, 85])),   // green
-		('b', Rgb([85, 255, 255])),  // aqua
-		('c', Rgb([255, 85, 85])),   // red
-		('d', Rgb([255, 85, 255])),  // light purple
-		('e', Rgb([255, 255, 85])),  // yellow
-		('f', Rgb([255, 255, 255])), // white
-	]
-	.iter()
-	.cloned()
-	.collect();
-
-	let line_height = scale.y * 1.15;
-	let mut x = 10.0;
-	let mut y = 50.0;
-
-	let mut current_color = Rgb([255, 255, 255]);
-	let mut bold = false;
-	let mut italic = false;
-	let mut strikethrough = false;
-	let mut underline = false;
+	pattern: &str,
+) -> Option<usize> {
+	let mut chars = text.char_indices();
+	while let Some((i, c)) = chars.next() {
+		if c == '\\' {
+			chars.next();
+		} else if text[i..].starts_with(pattern) {
+			return Some(i);
+		}
+	}
+	None
+}
+
+/// Resolves escapes and format codes, returning each line as characters paired with their style.
+/// The colors of `palette` are checked first, so an edition that uses a formatting code for a
+/// color gets the color.
+fn parse_text_with(
+	text: &str,
+	palette: &Palette,
+) -> Vec<Vec<(char, Style)>> {
+	let mut lines = vec![Vec::new()];
+	let mut style = Style::default();
 
 	let mut chars = text.chars().peekable();
 
 	while let Some(c) = chars.next() {
-		if c == '\\' {
-			if let Some(next_char) = chars.next() {
-				match next_char {
-					| 'n' => {
-						x = 10.0;
-						y += line_height;
-						continue;
-					},
-					| '&' => {
-						draw_character(
-							'&',
-							fonts,
-							image,
-							&mut x,
-							y,
-							scale,
-							bold,
-							italic,
-							strikethrough,
-							underline,
-							current_color,
-						);
-						continue;
-					},
-					| _ => {
-						draw_character(
-							'\\',
-							fonts,
-							image,
-							&mut x,
-							y,
-							scale,
-							bold,
-							italic,
-							strikethrough,
-							underline,
-							current_color,
-						);
-						continue;
-					},
-				}
-			} else {
-				draw_character(
-					'\\',
-					fonts,
-					image,
-					&mut x,
-					y,
-					scale,
-					bold,
-					italic,
-					strikethrough,
-					underline,
-					current_color,
-				);
-				continue;
+		let c = if c == '\\' {
+			match chars.next() {
+				| Some('n') => {
+					lines.push(Vec::new());
+					continue;
+				},
+				| Some(c @ ('&' | '{' | '}')) => c,
+				| _ => '\\',
 			}
 		} else if c == '&' || c == '§' {
 			if let Some(format_code) = chars.next() {
+				if let Some(color) = palette.color(format_code) {
+					style.color = color;
+					continue;
+				}
 				match format_code {
-					| 'l' => bold = true,
-					| 'o' => italic = true,
-					| 'm' => strikethrough = true,
-					| 'n' => underline = true,
-					| 'r' => {
-						bold = false;
-						italic = false;
-						strikethrough = false;
-						underline = false;
-						current_color = Rgb([255, 255, 255]);
-					},
-					| _ if colors.contains_key(&format_code) => {
-						current_color = colors[&format_code];
-					},
+					| 'l' => style.bold = true,
+					| 'o' => style.italic = true,
+					| 'm' => style.strikethrough = true,
+					| 'n' => style.underline = true,
+					| 'r' => style = Style::default(),
 					| _ => {},
 				}
 				continue;
 			}
+			c
+		} else {
+			c
+		};
+
+		lines.last_mut().unwrap().push((c, style));
+	}
+
+	lines
+}
+
+/// Sum of the advance widths of a parsed line, in pixels.
+fn line_width(
+	line: &[(char, Style)],
+	fonts: &HashMap<&str, Font>,
+	scale: Scale,
+) -> f32 {
+	line.iter()
+		.map(|&(c, style)| advance(c, style, fonts, scale))
+		.sum()
+}
+
+/// How far a character moves the next one, including the characters standing in for heads.
+fn advance(
+	c: char,
+	style: Style,
+	fonts: &HashMap<&str, Font>,
+	scale: Scale,
+) -> f32 {
+	if head::is_head_char(c) {
+		return head::HEAD_ADVANCE * scale.y / 8.0;
+	}
+	fonts[style.font_key()]
+		.glyph(c)
+		.scaled(scale)
+		.h_metrics()
+		.advance_width
+}
+
+/// A continuous underline or strikethrough drawn under a run of characters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Decoration {
+	kind:  &'static str,
+	start: f32,
+	end:   f32,
+	top:   f32,
+	color: Rgb<u8>,
+}
+
+/// Groups the decorated characters of a line into runs, each covering the full advance of its
+/// characters (spaces included) like vanilla does, one font pixel thick.
+fn decorations(
+	line: &[(char, Style)],
+	fonts: &HashMap<&str, Font>,
+	scale: Scale,
+	origin: (f32, f32),
+) -> Vec<Decoration> {
+	// size of one pixel of the font, which is 8 pixels tall
+	let unit = scale.y / 8.0;
+	let (mut x, y) = origin;
+	let mut runs: Vec<Decoration> = Vec::new();
+	let mut open: [Option<usize>; 2] = [None, None];
+
+	for &(c, style) in line {
+		let advance = advance(c, style, fonts, scale);
+
+		let kinds = [
+			("strikethrough", style.strikethrough, y - 3.5 * unit),
+			("underline", style.underline, y + unit),
+		];
+		for (i, (kind, enabled, top)) in kinds.into_iter().enumerate() {
+			if !enabled {
+				open[i] = None;
+				continue;
+			}
+
+			match open[i] {
+				| Some(run) if runs[run].color == style.color => runs[run].end = x + advance,
+				| _ => {
+					open[i] = Some(runs.len());
+					runs.push(Decoration {
+						kind,
+						start: x - unit,
+						end: x + advance,
+						top,
+						color: style.color,
+					});
+				},
+			}
 		}
 
-		draw_character(
-			c,
-			fonts,
+		x += advance;
+	}
+
+	runs
+}
+
+fn fill_rect(
+	image: &mut RgbImage,
+	(x0, y0): (f32, f32),
+	(x1, y1): (f32, f32),
+	color: Rgb<u8>,
+) {
+	let (width, height) = (image.width() as f32, image.height() as f32);
+	let (x0, x1) = (x0.round().max(0.0), x1.round().min(width));
+	let (y0, y1) = (y0.round().max(0.0), y1.round().min(height));
+
+	for py in y0 as u32..y1.max(y0) as u32 {
+		for px in x0 as u32..x1.max(x0) as u32 {
+			image.put_pixel(px, py, color);
+		}
+	}
+}
+
+fn draw_line(
+	line: &[(char, Style)],
+	fonts: &HashMap<&str, Font>,
+	image: &mut RgbImage,
+	origin: (f32, f32),
+	scale: Scale,
+) {
+	let mut x = origin.0;
+	for &(c, style) in line {
+		draw_character(c, fonts, image, &mut x, origin.1, scale, style);
+	}
+
+	let thickness = scale.y / 8.0;
+	for decoration in decorations(line, fonts, scale, origin) {
+		fill_rect(
 			image,
-			&mut x,
-			y,
-			scale,
-			bold,
-			italic,
-			strikethrough,
-			underline,
-			current_color,
+			(decoration.start, decoration.top),
+			(decoration.end, decoration.top + thickness),
+			decoration.color,
 		);
 	}
+}
+
+/// How a layout draws its text, beyond the fonts and the size.
+#[derive(Clone, Copy, Debug)]
+struct TextOptions<'a> {
+	palette:     &'a Palette,
+	align:       Align,
+	shadow:      bool,
+	/// Distance between lines as a multiple of the scale.
+	line_height: f32,
+}
+
+impl<'a> TextOptions<'a> {
+	/// How the game draws text in `layout`, where only the chat and tooltips have a shadow.
+	fn vanilla(
+		palette: &'a Palette,
+		layout: Layout,
+	) -> Self {
+		TextOptions {
+			palette,
+			align: Align::Left,
+			shadow: layout != Layout::Screenshot,
+			line_height: LINE_HEIGHT,
+		}
+	}
+}
+
+/// Vanilla draws shadows one font pixel down and right, at a quarter of the text's brightness,
+/// unless `palette` gives the color its own.
+fn shadow_line_with(
+	line: &[(char, Style)],
+	palette: &Palette,
+) -> Vec<(char, Style)> {
+	line.iter()
+		.map(|&(c, style)| {
+			let color = palette.shadow(style.color);
+			(c, Style { color, ..style })
+		})
+		.collect()
+}
+
+/// Draws `text` from `origin`, aligning lines between margins as wide as the one on the left.
+fn render_text(
+	text: &str,
+	fonts: &HashMap<&str, Font>,
+	image: &mut RgbImage,
+	scale: Scale,
+	origin: (f32, f32),
+	options: &TextOptions,
+) -> RgbImage {
+	let line_height = scale.y * options.line_height;
+	let unit = scale.y / 8.0;
+	let width = image.width() as f32 - origin.0 * 2.0;
+	let (text, alignments) = split_alignments(text, options.align);
+
+	for (i, line) in parse_text_with(&text, options.palette).iter().enumerate() {
+		let offset = alignments[i].offset(line_width(line, fonts, scale), width, unit);
+		let (x, y) = (origin.0 + offset, origin.1 + i as f32 * line_height);
+
+		if options.shadow {
+			draw_line(
+				&shadow_line_with(line, options.palette),
+				fonts,
+				image,
+				(x + unit, y + unit),
+				scale,
+			);
+		}
+		draw_line(line, fonts, image, (x, y), scale);
+	}
 	image.clone()
 }
 
@@ -217,20 +482,16 @@ fn draw_character(
 	x: &mut f32,
 	y: f32,
 	scale: Scale,
-	bold: bool,
-	italic: bool,
-	strikethrough: bool,
-	underline: bool,
-	color: Rgb<u8>,
+	style: Style,
 ) {
-	let font_key = match (bold, italic) {
-		| (true, true) => "bold_italic",
-		| (true, false) => "bold",
-		| (false, true) => "italic",
-		| (false, false) => "regular",
-	};
+	// heads are drawn into the space they leave once the line is done
+	if head::is_head_char(c) {
+		*x += advance(c, style, fonts, scale);
+		return;
+	}
 
-	let font = &fonts[font_key];
+	let color = style.color;
+	let font = &fonts[style.font_key()];
 	let glyph = font.glyph(c);
 	let scaled_glyph = glyph.scaled(scale);
 	let positioned_glyph = scaled_glyph.clone().positioned(point(*x, y));
@@ -256,47 +517,257 @@ fn draw_character(
 				image.put_pixel(px, py, blended_color);
 			}
 		});
+	}
 
-		if strikethrough {
-			let y_strike = y - scale.y / 3.0;
-			for px in bounding_box.min.x..bounding_box.max.x {
-				if px >= 0
-					&& y_strike >= 0.0
-					&& px < image.width() as i32
-					&& y_strike < image.height() as f32
-				{
-					image.put_pixel(px as u32, y_strike as u32, color);
-				}
+	*x += scaled_glyph.h_metrics().advance_width;
+}
+
+/// Renders `text` in the given layout, `gui_scale` works like the in game GUI scale setting.
+fn render(
+	text: &str,
+	fonts: &HashMap<&str, Font>,
+	background: &RgbImage,
+	layout: Layout,
+	gui_scale: u32,
+	options: &TextOptions,
+) -> RgbImage {
+	let unit = gui_scale as f32;
+	let scale = Scale::uniform(8.0 * unit);
+
+	match layout {
+		| Layout::Screenshot => {
+			let mut image = background.clone();
+			render_text(
+				text,
+				fonts,
+				&mut image,
+				scale,
+				(5.0 * unit, 25.0 * unit),
+				options,
+			)
+		},
+		| Layout::Chat => {
+			let line_count = split_lines(text).len();
+			let mut image = chat_backdrop(background, gui_scale, line_count, options.line_height);
+			render_text(text, fonts, &mut image, scale, (unit, 7.0 * unit), options)
+		},
+		| Layout::Tooltip => render_tooltip(text, fonts, background, gui_scale, options),
+	}
+}
+
+/// Size of the image `render` returns, without drawing it.
+fn output_size(
+	text: &str,
+	fonts: &HashMap<&str, Font>,
+	background: &RgbImage,
+	layout: Layout,
+	gui_scale: u32,
+	options: &TextOptions,
+) -> (u32, u32) {
+	match layout {
+		| Layout::Screenshot => background.dimensions(),
+		| Layout::Chat => chat_size(
+			background,
+			gui_scale,
+			split_lines(text).len(),
+			options.line_height,
+		),
+		| Layout::Tooltip => {
+			let (text, _) = split_alignments(text, options.align);
+			let lines = parse_text_with(&text, options.palette);
+			let (width, height) = tooltip_size(&lines, fonts, gui_scale, options.line_height);
+			(width * gui_scale, height * gui_scale)
+		},
+	}
+}
+
+/// Size of the chat box with `line_count` lines, which like the screen it is on can't be taller
+/// than the background at `gui_scale`. Lines that don't fit are cut off at the bottom.
+fn chat_size(
+	background: &RgbImage,
+	gui_scale: u32,
+	line_count: usize,
+	line_height: f32,
+) -> (u32, u32) {
+	let screen_height = (background.height() * gui_scale / 2).max(1);
+	let height = (line_count as f32 * 8.0 * gui_scale as f32 * line_height).ceil() as u32;
+	(320 * gui_scale, height.min(screen_height))
+}
+
+/// The chat box's translucent backdrop with room for `line_count` lines, where vanilla draws it
+/// just above the hotbar.
+fn chat_backdrop(
+	background: &RgbImage,
+	gui_scale: u32,
+	line_count: usize,
+	line_height: f32,
+) -> RgbImage {
+	let (width, height) = chat_size(background, gui_scale, line_count, line_height);
+	let top = (background.height() * gui_scale / 2).saturating_sub(height + 40 * gui_scale);
+
+	let mut image = backdrop(background, gui_scale, (0, top), (width, height));
+	for pixel in image.pixels_mut() {
+		pixel.0 = pixel.0.map(|channel| channel / 2);
+	}
+	image
+}
+
+/// Part of the background at `gui_scale`, repeated if the area is bigger than the background.
+fn backdrop(
+	background: &RgbImage,
+	gui_scale: u32,
+	(left, top): (u32, u32),
+	(width, height): (u32, u32),
+) -> RgbImage {
+	// the background screenshot was taken at gui scale 2
+	let zoomed = imageops::resize(
+		background,
+		(background.width() * gui_scale / 2).max(1),
+		(background.height() * gui_scale / 2).max(1),
+		FilterType::Nearest,
+	);
+	RgbImage::from_fn(width, height, |x, y| {
+		*zoomed.get_pixel((left + x) % zoomed.width(), (top + y) % zoomed.height())
+	})
+}
+
+fn blend_rect(
+	image: &mut RgbImage,
+	(x0, y0): (u32, u32),
+	(x1, y1): (u32, u32),
+	color: Rgb<u8>,
+	alpha: f32,
+) {
+	for y in y0..y1.min(image.height()) {
+		for x in x0..x1.min(image.width()) {
+			let pixel = image.get_pixel_mut(x, y);
+			for (channel, target) in pixel.0.iter_mut().zip(color.0) {
+				*channel = (*channel as f32 * (1.0 - alpha) + target as f32 * alpha) as u8;
 			}
 		}
+	}
+}
 
-		if underline {
-			let y_underline = y + scale.y / 10.0;
-			for px in bounding_box.min.x..bounding_box.max.x {
-				if px >= 0
-					&& y_underline >= 0.0
-					&& px < image.width() as i32
-					&& y_underline < image.height() as f32
-				{
-					image.put_pixel(px as u32, y_underline as u32, color);
-				}
-			}
+/// Gui pixels from one tooltip line to the next, vanilla's 10 at the default line height.
+fn tooltip_spacing(line_height: f32) -> f32 { 10.0 * (line_height / LINE_HEIGHT) }
+
+/// Size of the tooltip frame around `lines` in gui pixels, like vanilla's tooltip code.
+fn tooltip_size(
+	lines: &[Vec<(char, Style)>],
+	fonts: &HashMap<&str, Font>,
+	gui_scale: u32,
+	line_height: f32,
+) -> (u32, u32) {
+	let unit = gui_scale as f32;
+	let scale = Scale::uniform(8.0 * unit);
+
+	let text_width = lines
+		.iter()
+		.map(|line| (line_width(line, fonts, scale) / unit).ceil() as u32)
+		.max()
+		.unwrap_or(0);
+	let spacing = (lines.len() - 1) as f32 * tooltip_spacing(line_height);
+	let text_height = 8 + spacing.ceil() as u32 + if lines.len() > 1 { 2 } else { 0 };
+	(text_width + 8, text_height + 8)
+}
+
+/// Draws text in an item tooltip frame, with the gap vanilla leaves under the first line.
+fn render_tooltip(
+	text: &str,
+	fonts: &HashMap<&str, Font>,
+	background: &RgbImage,
+	gui_scale: u32,
+	options: &TextOptions,
+) -> RgbImage {
+	let unit = gui_scale as f32;
+	let scale = Scale::uniform(8.0 * unit);
+	let (text, alignments) = split_alignments(text, options.align);
+	let lines = parse_text_with(&text, options.palette);
+	let (width, height) = tooltip_size(&lines, fonts, gui_scale, options.line_height);
+	let widths: Vec<f32> = lines
+		.iter()
+		.map(|line| line_width(line, fonts, scale))
+		.collect();
+	let text_width = widths.iter().copied().fold(0.0, f32::max);
+
+	let zoomed_size = (
+		background.width() * gui_scale / 2,
+		background.height() * gui_scale / 2,
+	);
+	let origin = (
+		zoomed_size.0.saturating_sub(width * gui_scale) / 2,
+		zoomed_size.1.saturating_sub(height * gui_scale) / 2,
+	);
+	let mut image = backdrop(
+		background,
+		gui_scale,
+		origin,
+		(width * gui_scale, height * gui_scale),
+	);
+
+	let mut rect = |(x0, y0): (u32, u32), (x1, y1): (u32, u32), color: [u8; 3], alpha: u8| {
+		blend_rect(
+			&mut image,
+			(x0 * gui_scale, y0 * gui_scale),
+			(x1 * gui_scale, y1 * gui_scale),
+			Rgb(color),
+			alpha as f32 / 255.0,
+		)
+	};
+
+	// background, with the corners left out
+	let fill = [0x10, 0x00, 0x10];
+	rect((1, 0), (width - 1, 1), fill, 0xf0);
+	rect((1, height - 1), (width - 1, height), fill, 0xf0);
+	rect((1, 1), (width - 1, height - 1), fill, 0xf0);
+	rect((0, 1), (1, height - 1), fill, 0xf0);
+	rect((width - 1, 1), (width, height - 1), fill, 0xf0);
+
+	// border fading from purple at the top to a darker purple at the bottom
+	let (top_color, bottom_color) = ([0x50, 0x00, 0xff], [0x28, 0x00, 0x7f]);
+	rect((1, 1), (width - 1, 2), top_color, 0x50);
+	rect((1, height - 2), (width - 1, height - 1), bottom_color, 0x50);
+	for y in 2..height - 2 {
+		let t = (y - 2) as f32 / (height - 4).max(1) as f32;
+		let color = [0, 1, 2]
+			.map(|i| (top_color[i] as f32 * (1.0 - t) + bottom_color[i] as f32 * t).round() as u8);
+		rect((1, y), (2, y + 1), color, 0x50);
+		rect((width - 2, y), (width - 1, y + 1), color, 0x50);
+	}
+
+	let spacing = tooltip_spacing(options.line_height);
+	for (i, line) in lines.iter().enumerate() {
+		let top = 4.0 + i as f32 * spacing + if i > 0 { 2.0 } else { 0.0 };
+		let offset = alignments[i].offset(widths[i], text_width, unit);
+		let (x, y) = (4.0 * unit + offset, (top + 7.0) * unit);
+		if options.shadow {
+			draw_line(
+				&shadow_line_with(line, options.palette),
+				fonts,
+				&mut image,
+				(x + unit, y + unit),
+				scale,
+			);
 		}
+		draw_line(line, fonts, &mut image, (x, y), scale);
 	}
 
-	*x += scaled_glyph.h_metrics().advance_width;
+	image
 }
 
-pub fn start() {
-	let current_dir = std::env::current_dir().expect("Failed to get current directory");
-	println!("Checking for font files in directory: {:?}", current_dir);
+fn load_background() -> RgbImage {
+	load_from_memory(include_bytes!("assets/background.png"))
+		.expect("Failed to load background image")
+		.to_rgb8()
+}
 
+fn load_fonts() -> HashMap<&'static str, Font<'static>> {
 	let font_data_regular = include_bytes!("assets/MinecraftRegular.otf");
 	let font_data_bold = include_bytes!("assets/MinecraftBold.otf");
 	let font_data_italic = include_bytes!("assets/MinecraftItalic.otf");
 	let font_data_bold_italic = include_bytes!("assets/MinecraftBoldItalic.otf");
 
-	let fonts = HashMap::from([
+	HashMap::from([
 		(
 			"regular",
 			Font::try_from_bytes(font_data_regular as &[u8]).expect("Error loading regular font"),
@@ -314,26 +785,52 @@ pub fn start() {
 			Font::try_from_bytes(font_data_bold_italic as &[u8])
 				.expect("Error loading bold italic font"),
 		),
-	]);
+	])
+}
 
-	let background_image = open("../assets/background.png")
-		.expect("Failed to load background image")
-		.to_rgb8();
+/// Waits for the next key press, ignoring releases and other events.
+fn next_key() -> KeyCode {
+	loop {
+		if event::poll(Duration::from_millis(100)).unwrap() {
+			if let Event::Key(key_event) = event::read().unwrap() {
+				if key_event.kind == KeyEventKind::Press {
+					return key_event.code;
+				}
+			}
+		}
+	}
+}
+
+/// Redraws the terminal with the part of `image` holding `rendered`, above the text being typed.
+fn print_terminal_preview(
+	image: &RgbImage,
+	rendered: &str,
+	typed: &str,
+	renderer: &Renderer,
+) {
+	let (columns, rows) = size().unwrap_or((80, 24));
+	let margin = renderer.scale().y / 2.0;
+	let cropped = crop_to_text(image, renderer.text_bounds(rendered), margin);
+	// leave room for the input line below the preview
+	let preview = fit(&cropped, columns as u32, rows.saturating_sub(2) as u32);
+
+	print!("\x1b[H\x1b[2J{}\r\n{}", half_blocks(&preview), typed);
+	stdout().flush().unwrap();
+}
+
+pub fn start() {
+	let current_dir = std::env::current_dir().expect("Failed to get current directory");
+	println!("Checking for font files in directory: {:?}", current_dir);
+
+	let background_image = load_background();
 	let (width, height) = background_image.dimensions();
-	let mut image = background_image.clone();
 
-	let scale = Scale::uniform(16.0);
+	let mut renderer = Renderer::new()
+		.with_scale(16.0)
+		.with_margins(10.0, 36.0)
+		.with_background(Some(background_image));
 
-	let mut text = String::new();
-	let mut window = Window::new(
-		"Text Renderer",
-		width as usize,
-		height as usize,
-		WindowOptions::default(),
-	)
-	.unwrap_or_else(|e| {
-		panic!("{}", e);
-	});
+	let mut shadow = false;
 
 	println!(
 		"\x1b[1mHelp Menu:\x1b[0m\n\
@@ -360,63 +857,145 @@ pub fn start() {
         \t\x1b[9m&m or §m: Strikethrough\x1b[0m\n\
         \t\x1b[4m&n or §n: Underline\x1b[0m\n\
         \t&r or §r: Reset all formatting\n\
+        \nBedrock Edition Colors:\n\
+        \t&g Minecoin Gold, &h Quartz, &i Iron, &j Netherite, &m Redstone, &n Copper,\n\
+        \t&p Gold, &q Emerald, &s Diamond, &t Lapis, &u Amethyst, &v Resin\n\
+        \t(Bedrock has no strikethrough or underline, so &m and &n are colors there)\n\
         \nSpecial Characters:\n\
         \t\\& for &\n\
         \t\\§ for §\n\
         \t\\\\ for \\\n\
-        \t\\n for new line\n"
+        \t\\n for new line\n\
+        \t\\{{ for {{\n\
+        \nPlaceholders:\n\
+        \t{{name}}: replaced with a variable\n\
+        \t{{translate:key|arg|...}}: translated with the language file\n\
+        \t{{head:Name}}: the head of the player whose skin is Name.png in the skins folder\n\
+        \t{{align:center}}: aligns this line and the ones after it, also left and right\n\
+        \nPress Tab to toggle the text shadow\n"
+	);
+
+	let palette = prompt_palette();
+	renderer = renderer.with_palette(palette.clone());
+
+	println!("\nHow should lines be aligned?");
+	let options: Vec<&str> = Align::ALL.iter().map(Align::name).collect();
+	let align = Align::ALL[menu(options) as usize - 1];
+	renderer = renderer.with_align(align);
+
+	let language_path = input(
+		"\nPath to a language file like en_us.json for {translate:key|arg} (leave empty to skip):",
+		true,
+	);
+	let language = if language_path.is_empty() {
+		HashMap::new()
+	} else {
+		Translator::load_language(&language_path).unwrap_or_else(|e| {
+			println!("\x1b[31m{}\x1b[0m", e);
+			HashMap::new()
+		})
+	};
+	let variables = input(
+		"\nVariables for {name} placeholders, as name=value separated by commas (leave empty to \
+		 skip):",
+		true,
 	);
+	let translator = Translator::new(language, Translator::parse_variables(&variables));
+
+	let skins = input(
+		"\nFolder with <name>.png skins for {head:Name} (leave empty to skip):",
+		true,
+	);
+	renderer = renderer.with_skins((!skins.is_empty()).then(|| PathBuf::from(skins)));
+
+	println!("\nWhat should the message start as?");
+	let mut text = match menu(vec![
+		"Nothing, type it from scratch",
+		"The text in the clipboard",
+	]) {
+		| 2 => clipboard_text()
+			.map(|pasted| import(&pasted, &palette))
+			.unwrap_or_else(|| {
+				println!("\x1b[31mThe clipboard doesn't contain any text\x1b[0m");
+				String::new()
+			}),
+		| _ => String::new(),
+	};
+
+	println!("\nWhere should the preview be shown?");
+	// without a display, e.g. over SSH, the preview is drawn in the terminal instead
+	let mut window = match menu(vec!["Window", "Terminal"]) {
+		| 2 => None,
+		| _ => Window::new(
+			"Text Renderer",
+			width as usize,
+			height as usize,
+			WindowOptions::default(),
+		)
+		.inspect_err(|e| {
+			println!(
+				"\x1b[31mFailed to open a window ({}), using the terminal instead\x1b[0m",
+				e
+			)
+		})
+		.ok(),
+	};
+	println!();
 
 	enable_raw_mode().expect("Failed to enable raw mode");
+	if window.is_none() {
+		execute!(stdout(), EnterAlternateScreen, Hide).expect("Failed to set up the terminal");
+	}
 
-	loop {
-		if event::poll(Duration::from_millis(100)).unwrap() {
-			if let Event::Key(key_event) = event::read().unwrap() {
-				if key_event.kind == KeyEventKind::Press {
-					match key_event.code {
-						| KeyCode::Char(c) => {
-							text.push(c);
-						},
-						| KeyCode::Backspace => {
-							text.pop();
-						},
-						| KeyCode::Enter => {
-							break;
-						},
-						| _ => {},
-					}
-
-					print!("\r\x1b[2K{}", text);
-					stdout().flush().unwrap();
-
-					image
-						.copy_from(&background_image, 0, 0)
-						.expect("Failed to copy background image");
-
-					let rendered_image = render_text(&text, &fonts, &mut image, scale);
-					let buffer: Vec<u32> = rendered_image
-						.pixels()
-						.map(|p| {
-							let [r, g, b] = p.0;
-							((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
-						})
-						.collect();
-					window
-						.update_with_buffer(
-							&buffer,
-							image.width() as usize,
-							image.height() as usize,
-						)
-						.unwrap();
-				}
-			}
+	let image = loop {
+		let resolved = translator.resolve(&text);
+		let image: RgbImage = renderer.render(&resolved).convert();
+
+		match window.as_mut() {
+			| Some(window) => {
+				print!("\r\x1b[2K{}", text);
+				stdout().flush().unwrap();
+
+				let buffer: Vec<u32> = image
+					.pixels()
+					.map(|p| {
+						let [r, g, b] = p.0;
+						((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+					})
+					.collect();
+				window
+					.update_with_buffer(&buffer, image.width() as usize, image.height() as usize)
+					.unwrap();
+			},
+			| None => print_terminal_preview(&image, &resolved, &text, &renderer),
 		}
-	}
 
+		match next_key() {
+			| KeyCode::Char(c) => {
+				text.push(c);
+			},
+			| KeyCode::Backspace => {
+				text.pop();
+			},
+			| KeyCode::Enter => {
+				break image;
+			},
+			| KeyCode::Tab => {
+				shadow = !shadow;
+				renderer = renderer.with_shadow(shadow);
+			},
+			| _ => {},
+		}
+	};
+
+	if window.is_none() {
+		execute!(stdout(), Show, LeaveAlternateScreen).expect("Failed to restore the terminal");
+		print!("{}", text);
+	}
 	disable_raw_mode().expect("Failed to disable raw mode");
 
 	println!();
-	let save_type = menu(vec!["Save to clipboard", "Save as file"]);
+	let save_type = menu(vec!["Save to clipboard", "Save as file", "Save as SVG"]);
 
 	match save_type {
 		| 1 => {
@@ -426,8 +1005,276 @@ pub fn start() {
 			let path = input("\nEnter the filename to save the image as:", true);
 			save(&image, &path.add(".png"), save_type);
 		},
+		| 3 => {
+			let mode = match menu(vec!["Outlined paths", "Pixel rectangles"]) {
+				| 2 => SvgMode::Pixel,
+				| _ => SvgMode::Outline,
+			};
+			let path = input("\nEnter the filename to save the svg as:", true);
+			let svg = render_svg(&translator.resolve(&text), &renderer, mode);
+			save_to_output(&path.add(".svg"), |path| fs::write(path, svg));
+		},
 		| _ => {},
 	}
 
+	println!("\nExport the formatted text too?");
+	let mut options = vec!["No"];
+	options.extend(TextFormat::ALL.iter().map(TextFormat::name));
+	let choice = menu(options) as usize;
+	if choice > 1 {
+		let exported = export(
+			&translator.resolve(&text),
+			TextFormat::ALL[choice - 2],
+			&palette,
+		);
+		println!("\n{}", exported);
+		if save_type == 1 {
+			// the clipboard holds one thing at a time, and the image is already in it
+			println!("\nThe image is in the clipboard, so the text was only printed");
+		} else {
+			copy_text_to_clipboard(&exported);
+		}
+	}
+
 	main()
 }
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use image::{Rgb, RgbImage, open};
+
+	use super::*;
+
+	// max difference allowed per color channel before a pixel counts as changed
+	const TOLERANCE: u8 = 8;
+
+	// (name, text, shadow) rendered and compared against `assets/golden/<name>.png`
+	const CORPUS: &[(&str, &str, bool)] = &[
+		(
+			"colors",
+			"&00&11&22&33&44&55&66&77\\n&88&99&aa&bb&cc&dd&ee&ff",
+			false,
+		),
+		("section_sign", "§cRed §aGreen §9Blue", false),
+		("bold", "&lBold text", false),
+		("italic", "&oItalic text", false),
+		("strikethrough", "&mStruck text", false),
+		("underline", "&nUnderlined text", false),
+		("bold_italic", "&l&oBold italic", false),
+		("all_styles", "&l&o&m&n&6Everything at once", false),
+		("reset", "&c&lRed bold &rplain again", false),
+		("unknown_code", "&zUnknown &kcodes", false),
+		("escapes", "\\& \\§ \\\\ \\x trailing\\", false),
+		("newlines", "first\\n&esecond\\n\\nfourth", false),
+		(
+			"wrapping",
+			"&bThis line is far too long to fit inside the canvas and keeps going past the edge",
+			false,
+		),
+		(
+			"decorated_spaces",
+			"&nunder lined&r &mstruck through",
+			false,
+		),
+		("shadow", "&e&lShadow &r&n&cunder&9line &mstrike", true),
+	];
+
+	fn golden_dir() -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/rust/assets/golden")
+	}
+
+	fn diff_dir() -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden-diff")
+	}
+
+	fn render(
+		text: &str,
+		shadow: bool,
+	) -> RgbImage {
+		let fonts = load_fonts();
+		let mut image = RgbImage::from_pixel(400, 120, Rgb([48, 48, 48]));
+		let palette = Palette::java();
+		let options = TextOptions {
+			shadow,
+			..TextOptions::vanilla(&palette, Layout::Screenshot)
+		};
+		render_text(
+			text,
+			&fonts,
+			&mut image,
+			Scale::uniform(16.0),
+			(10.0, 50.0),
+			&options,
+		)
+	}
+
+	// returns the number of pixels outside of `TOLERANCE` and an image highlighting them
+	fn compare(
+		actual: &RgbImage,
+		expected: &RgbImage,
+	) -> (usize, RgbImage) {
+		let mut diff = RgbImage::new(actual.width(), actual.height());
+		let mut mismatched = 0;
+
+		for (x, y, pixel) in actual.enumerate_pixels() {
+			let reference = expected.get_pixel(x, y);
+			let changed = pixel
+				.0
+				.iter()
+				.zip(reference.0.iter())
+				.any(|(a, b)| a.abs_diff(*b) > TOLERANCE);
+
+			if changed {
+				mismatched += 1;
+				diff.put_pixel(x, y, Rgb([255, 0, 0]));
+			} else {
+				let [r, g, b] = pixel.0;
+				diff.put_pixel(x, y, Rgb([r / 4, g / 4, b / 4]));
+			}
+		}
+
+		(mismatched, diff)
+	}
+
+	/// Run with `UPDATE_GOLDEN=1` to overwrite the reference images after an intentional change.
+	#[test]
+	fn matches_golden_images() {
+		let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+		let mut failures = Vec::new();
+
+		for (name, text, shadow) in CORPUS {
+			let actual = render(text, *shadow);
+			let golden_path = golden_dir().join(format!("{}.png", name));
+
+			if update {
+				fs::create_dir_all(golden_dir()).unwrap();
+				actual.save(&golden_path).unwrap();
+				continue;
+			}
+
+			let expected = match open(&golden_path) {
+				| Ok(image) => image.to_rgb8(),
+				| Err(_) => {
+					failures.push(format!("{}: missing reference image", name));
+					continue;
+				},
+			};
+
+			if expected.dimensions() != actual.dimensions() {
+				failures.push(format!(
+					"{}: size changed from {:?} to {:?}",
+					name,
+					expected.dimensions(),
+					actual.dimensions()
+				));
+				continue;
+			}
+
+			let (mismatched, diff) = compare(&actual, &expected);
+			if mismatched > 0 {
+				fs::create_dir_all(diff_dir()).unwrap();
+				let diff_path = diff_dir().join(format!("{}.png", name));
+				diff.save(&diff_path).unwrap();
+				actual
+					.save(diff_dir().join(format!("{}.actual.png", name)))
+					.unwrap();
+				failures.push(format!(
+					"{}: {} pixels differ, see {}",
+					name,
+					mismatched,
+					diff_path.display()
+				));
+			}
+		}
+
+		assert!(
+			failures.is_empty(),
+			"golden images differ (rerun with UPDATE_GOLDEN=1 if intended):\n{}",
+			failures.join("\n")
+		);
+	}
+
+	#[test]
+	fn decorations_span_whole_runs() {
+		let fonts = load_fonts();
+		let scale = Scale::uniform(16.0);
+		let line = &parse_text_with("&na b&cc &md", &Palette::java())[0];
+		let runs = decorations(line, &fonts, scale, (10.0, 50.0));
+
+		// "a b" in white, "c " in red, then "d" both underlined and struck in red
+		let underlines: Vec<_> = runs.iter().filter(|run| run.kind == "underline").collect();
+		assert_eq!(underlines.len(), 2);
+		assert_eq!(underlines[0].start, 8.0);
+		assert_eq!(underlines[0].end, underlines[1].start + 2.0);
+		assert!((underlines[1].end - 10.0 - line_width(line, &fonts, scale)).abs() < 0.01);
+		assert_eq!(underlines[0].top, 52.0);
+
+		let strikethroughs: Vec<_> = runs
+			.iter()
+			.filter(|run| run.kind == "strikethrough")
+			.collect();
+		assert_eq!(strikethroughs.len(), 1);
+		assert_eq!(strikethroughs[0].top, 43.0);
+	}
+
+	#[test]
+	fn compare_ignores_small_differences() {
+		let a = RgbImage::from_pixel(4, 4, Rgb([100, 100, 100]));
+		let mut b = a.clone();
+		b.put_pixel(0, 0, Rgb([100 + TOLERANCE, 100, 100]));
+		assert_eq!(compare(&a, &b).0, 0);
+
+		b.put_pixel(1, 1, Rgb([100 + TOLERANCE + 1, 100, 100]));
+		assert_eq!(compare(&a, &b).0, 1);
+	}
+
+	#[test]
+	fn tooltip_leaves_gap_under_name() {
+		let fonts = load_fonts();
+		let background = load_background();
+		let palette = Palette::java();
+		let options = TextOptions::vanilla(&palette, Layout::Tooltip);
+		let render = |text: &str, background: &RgbImage, gui_scale: u32| {
+			super::render(
+				text,
+				&fonts,
+				background,
+				Layout::Tooltip,
+				gui_scale,
+				&options,
+			)
+		};
+
+		let one_line = render("&bDiamond", &background, 1);
+		assert_eq!(one_line.height(), 8 + 8);
+		let three_lines = render("Name\\nLore\\nLore", &background, 2);
+		assert_eq!(three_lines.height(), (8 + 2 * 10 + 2 + 8) * 2);
+		// the corners are left out of the frame, so they show the background
+		let corner = render("A", &RgbImage::new(4, 4), 1);
+		assert_eq!(corner.get_pixel(0, 0), &Rgb([0, 0, 0]));
+		assert_ne!(corner.get_pixel(1, 1), &Rgb([0, 0, 0]));
+	}
+
+	#[test]
+	fn chat_is_no_taller_than_the_screen() {
+		let fonts = load_fonts();
+		let background = load_background();
+		let text = "a\\n".repeat(1000);
+		let palette = Palette::java();
+		let options = TextOptions::vanilla(&palette, Layout::Chat);
+
+		let size = output_size(&text, &fonts, &background, Layout::Chat, 8, &options);
+		assert_eq!(size, (320 * 8, background.height() * 4));
+		let image = super::render(&text, &fonts, &background, Layout::Chat, 8, &options);
+		assert_eq!(image.dimensions(), size);
+	}
+
+	#[test]
+	fn trims_numbers() {
+		assert_eq!(format_number(1.0), "1");
+		assert_eq!(format_number(1.5f32), "1.5");
+		assert_eq!(format_number(-2.504), "-2.5");
+	}
+}
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use super::find_unescaped;
+use super::measure::split_lines;
+
+/// How a line is placed in the space the text is drawn in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+	#[default]
+	Left,
+	Center,
+	Right,
+}
+
+impl FromStr for Align {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.trim().to_lowercase().as_str() {
+			| "left" => Ok(Align::Left),
+			| "center" | "centre" => Ok(Align::Center),
+			| "right" => Ok(Align::Right),
+			| _ => Err(format!("Unknown alignment: {}", s)),
+		}
+	}
+}
+
+impl Align {
+	pub const ALL: [Align; 3] = [Align::Left, Align::Center, Align::Right];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			| Align::Left => "Left",
+			| Align::Center => "Center",
+			| Align::Right => "Right",
+		}
+	}
+
+	/// How far right a line `line_width` wide starts in `width`, in whole font pixels of `unit`
+	/// size like the game places centered text.
+	pub fn offset(
+		&self,
+		line_width: f32,
+		width: f32,
+		unit: f32,
+	) -> f32 {
+		let free = (width - line_width).max(0.0);
+		match self {
+			| Align::Left => 0.0,
+			| Align::Center => (free / 2.0 / unit).floor() * unit,
+			| Align::Right => (free / unit).floor() * unit,
+		}
+	}
+}
+
+/// Removes `{align:center}` style directives, returning the text and the alignment of each of
+/// its lines. A directive aligns its own line and the ones after it, until the next directive,
+/// and lines before the first one use `default`.
+pub fn split_alignments(
+	text: &str,
+	default: Align,
+) -> (String, Vec<Align>) {
+	let mut align = default;
+	let mut alignments = Vec::new();
+
+	let lines: Vec<String> = split_lines(text)
+		.into_iter()
+		.map(|line| {
+			let mut stripped = String::new();
+			let mut rest = line.as_str();
+
+			while let Some(start) = find_unescaped(rest, "{align:") {
+				let after = &rest[start + "{align:".len()..];
+				let Some((name, remaining)) = after.split_once('}') else {
+					break;
+				};
+				match name.parse() {
+					| Ok(directive) => {
+						align = directive;
+						stripped.push_str(&rest[..start]);
+					},
+					// not a directive, so it stays as typed
+					| Err(_) => stripped.push_str(&rest[..rest.len() - remaining.len()]),
+				}
+				rest = remaining;
+			}
+
+			stripped.push_str(rest);
+			alignments.push(align);
+			stripped
+		})
+		.collect();
+
+	(lines.join("\\n"), alignments)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn directives_carry_over_to_later_lines() {
+		let (text, alignments) = split_alignments(
+			"a\\n{align:center}&6b{align:nowhere}\\nc\\n&r{align:right}d",
+			Align::Left,
+		);
+		assert_eq!(text, "a\\n&6b{align:nowhere}\\nc\\n&rd");
+		assert_eq!(
+			alignments,
+			vec![Align::Left, Align::Center, Align::Center, Align::Right]
+		);
+
+		let (_, alignments) = split_alignments("a\\nb", Align::Right);
+		assert_eq!(alignments, vec![Align::Right, Align::Right]);
+
+		let (text, alignments) = split_alignments("\\{align:right}a", Align::Left);
+		assert_eq!(text, "\\{align:right}a");
+		assert_eq!(alignments, vec![Align::Left]);
+	}
+
+	#[test]
+	fn offsets_snap_to_font_pixels() {
+		assert_eq!(Align::Left.offset(10.0, 100.0, 2.0), 0.0);
+		assert_eq!(Align::Center.offset(10.0, 100.0, 2.0), 44.0);
+		assert_eq!(Align::Center.offset(12.0, 100.0, 4.0), 44.0);
+		assert_eq!(Align::Right.offset(10.0, 100.0, 2.0), 90.0);
+		// lines wider than the space start where left aligned ones do
+		assert_eq!(Align::Right.offset(120.0, 100.0, 2.0), 0.0);
+	}
+}
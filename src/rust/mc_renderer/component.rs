@@ -0,0 +1,360 @@
+use image::Rgb;
+use serde_json::{Map, Value};
+
+use super::palette::Palette;
+
+/// Names JSON text components use for the legacy colors.
+pub const COLOR_NAMES: [(&str, char); 16] = [
+	("black", '0'),
+	("dark_blue", '1'),
+	("dark_green", '2'),
+	("dark_aqua", '3'),
+	("dark_red", '4'),
+	("dark_purple", '5'),
+	("gold", '6'),
+	("gray", '7'),
+	("dark_gray", '8'),
+	("blue", '9'),
+	("green", 'a'),
+	("aqua", 'b'),
+	("red", 'c'),
+	("light_purple", 'd'),
+	("yellow", 'e'),
+	("white", 'f'),
+];
+
+/// Formatting of a JSON text component, inherited by its children.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComponentStyle {
+	pub color:         Option<char>,
+	pub bold:          bool,
+	pub italic:        bool,
+	pub underlined:    bool,
+	pub strikethrough: bool,
+}
+
+impl ComponentStyle {
+	fn apply(
+		&self,
+		object: &Map<String, Value>,
+		palette: &Palette,
+	) -> Self {
+		let flag = |key: &str, inherited: bool| {
+			object
+				.get(key)
+				.and_then(|value| value.as_bool())
+				.unwrap_or(inherited)
+		};
+
+		ComponentStyle {
+			// white is the renderer's default, so it is stored as no color
+			color:         match object
+				.get("color")
+				.and_then(Value::as_str)
+				.and_then(|name| color_code(name, palette))
+			{
+				| Some(code) => Some(code).filter(|code| *code != 'f'),
+				| None => self.color,
+			},
+			bold:          flag("bold", self.bold),
+			italic:        flag("italic", self.italic),
+			underlined:    flag("underlined", self.underlined),
+			strikethrough: flag("strikethrough", self.strikethrough),
+		}
+	}
+
+	/// The codes that switch the renderer from any style to this one. Decorations are left out
+	/// when `palette` uses their codes for colors.
+	fn codes(
+		&self,
+		palette: &Palette,
+	) -> String {
+		let mut codes = String::from("&r");
+		if let Some(color) = self.color {
+			codes.push('&');
+			codes.push(color);
+		}
+		for (enabled, code) in [
+			(self.bold, 'l'),
+			(self.italic, 'o'),
+			(self.underlined, 'n'),
+			(self.strikethrough, 'm'),
+		] {
+			if enabled && palette.color(code).is_none() {
+				codes.push('&');
+				codes.push(code);
+			}
+		}
+		codes
+	}
+}
+
+/// Looks up a color by name, or the closest color of `palette` for `#rrggbb` values.
+pub fn color_code(
+	name: &str,
+	palette: &Palette,
+) -> Option<char> {
+	if let Some(hex) = name.strip_prefix('#') {
+		let value = u32::from_str_radix(hex, 16).ok()?;
+		let target = [(value >> 16) as u8, (value >> 8) as u8, value as u8];
+		let distance = |color: &Rgb<u8>| {
+			color
+				.0
+				.iter()
+				.zip(target)
+				.map(|(a, b)| (*a as i32 - b as i32).pow(2))
+				.sum::<i32>()
+		};
+		return palette
+			.colors()
+			.into_iter()
+			.min_by_key(|(_, color)| distance(color))
+			.map(|(code, _)| code);
+	}
+
+	COLOR_NAMES
+		.iter()
+		.find(|(color, _)| *color == name)
+		.map(|(_, code)| *code)
+}
+
+/// Converts a JSON text component into the `&` formatted text the renderer draws with
+/// `palette`, starting from `base` the way vanilla styles item names and lore.
+pub fn to_legacy(
+	component: &Value,
+	base: &ComponentStyle,
+	palette: &Palette,
+) -> String {
+	let mut legacy = String::new();
+	let mut current = ComponentStyle::default();
+	write_component(component, base, palette, &mut legacy, &mut current);
+	legacy
+}
+
+fn write_component(
+	component: &Value,
+	style: &ComponentStyle,
+	palette: &Palette,
+	legacy: &mut String,
+	current: &mut ComponentStyle,
+) {
+	match component {
+		| Value::Array(items) => {
+			// the first element is the parent of the rest
+			let Some((first, rest)) = items.split_first() else {
+				return;
+			};
+			let parent = match first {
+				| Value::Object(object) => style.apply(object, palette),
+				| _ => style.clone(),
+			};
+			write_component(first, style, palette, legacy, current);
+			for item in rest {
+				write_component(item, &parent, palette, legacy, current);
+			}
+		},
+		| Value::Object(object) => {
+			let style = style.apply(object, palette);
+
+			if let Some(text) = object.get("text") {
+				write_component(text, &style, palette, legacy, current);
+			} else if let Some(key) = object.get("translate").and_then(Value::as_str) {
+				let format = object
+					.get("fallback")
+					.and_then(Value::as_str)
+					.unwrap_or(key);
+				let args = object
+					.get("with")
+					.and_then(Value::as_array)
+					.map(Vec::as_slice)
+					.unwrap_or_default();
+				write_translation(format, args, &style, palette, legacy, current);
+			} else if let Some(key) = object.get("keybind").and_then(Value::as_str) {
+				write_text(key, &style, palette, legacy, current);
+			}
+
+			if let Some(extra) = object.get("extra").and_then(Value::as_array) {
+				for child in extra {
+					write_component(child, &style, palette, legacy, current);
+				}
+			}
+		},
+		| Value::String(text) => write_text(text, style, palette, legacy, current),
+		| Value::Number(number) => write_text(&number.to_string(), style, palette, legacy, current),
+		| Value::Bool(value) => write_text(&value.to_string(), style, palette, legacy, current),
+		| Value::Null => {},
+	}
+}
+
+/// Writes a translation's format string, filling `%s` and `%1$s` with components from `args`.
+fn write_translation(
+	format: &str,
+	args: &[Value],
+	style: &ComponentStyle,
+	palette: &Palette,
+	legacy: &mut String,
+	current: &mut ComponentStyle,
+) {
+	let mut literal = String::new();
+	let mut next_argument = 0;
+	let mut chars = format.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			literal.push(c);
+			continue;
+		}
+
+		let mut digits = String::new();
+		while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+			digits.push(digit);
+		}
+		if !digits.is_empty() && chars.next_if_eq(&'$').is_none() {
+			literal.push('%');
+			literal.push_str(&digits);
+			continue;
+		}
+
+		let index = match chars.next_if(|c| *c == 's' || *c == '%') {
+			| Some('%') => {
+				literal.push('%');
+				continue;
+			},
+			| Some(_) if digits.is_empty() => {
+				next_argument += 1;
+				next_argument - 1
+			},
+			| Some(_) => digits.parse::<usize>().unwrap_or(0).saturating_sub(1),
+			| None => {
+				literal.push('%');
+				continue;
+			},
+		};
+
+		write_text(&literal, style, palette, legacy, current);
+		literal.clear();
+		if let Some(arg) = args.get(index) {
+			write_component(arg, style, palette, legacy, current);
+		}
+	}
+
+	write_text(&literal, style, palette, legacy, current);
+}
+
+fn write_text(
+	text: &str,
+	style: &ComponentStyle,
+	palette: &Palette,
+	legacy: &mut String,
+	current: &mut ComponentStyle,
+) {
+	if text.is_empty() {
+		return;
+	}
+
+	if style != current {
+		legacy.push_str(&style.codes(palette));
+		*current = style.clone();
+	}
+
+	for c in text.chars() {
+		match c {
+			| '&' => legacy.push_str("\\&"),
+			// braces would start placeholders
+			| '{' => legacy.push_str("\\{"),
+			| '\\' => legacy.push_str("\\\\"),
+			| '\n' => legacy.push_str("\\n"),
+			| _ => legacy.push(c),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn converts_styles_and_children() {
+		let component = json!({
+			"text": "Hello ",
+			"color": "gold",
+			"bold": true,
+			"extra": [{"text": "world", "bold": false}, "!"]
+		});
+		assert_eq!(
+			to_legacy(&component, &ComponentStyle::default(), &Palette::java()),
+			"&r&6&lHello &r&6world&r&6&l!"
+		);
+	}
+
+	#[test]
+	fn inherits_base_style() {
+		let base = ComponentStyle {
+			color: Some('5'),
+			italic: true,
+			..Default::default()
+		};
+		assert_eq!(
+			to_legacy(&json!("Lore"), &base, &Palette::java()),
+			"&r&5&oLore"
+		);
+		assert_eq!(
+			to_legacy(
+				&json!({"text": "Plain", "italic": false, "color": "white"}),
+				&base,
+				&Palette::java()
+			),
+			"Plain"
+		);
+	}
+
+	#[test]
+	fn fills_translations() {
+		let component = json!({
+			"translate": "chat.type.text",
+			"fallback": "<%s> %s",
+			"with": [{"text": "Steve", "color": "red"}, "hi"]
+		});
+		assert_eq!(
+			to_legacy(&component, &ComponentStyle::default(), &Palette::java()),
+			"<&r&cSteve&r> hi"
+		);
+	}
+
+	#[test]
+	fn escapes_special_characters() {
+		assert_eq!(
+			to_legacy(
+				&json!("a&b\\c\nd"),
+				&ComponentStyle::default(),
+				&Palette::java()
+			),
+			"a\\&b\\\\c\\nd"
+		);
+	}
+
+	#[test]
+	fn maps_hex_colors_to_closest_code() {
+		let java = Palette::java();
+		assert_eq!(color_code("#ff5050", &java), Some('c'));
+		assert_eq!(color_code("dark_aqua", &java), Some('3'));
+		assert_eq!(color_code("pink", &java), None);
+		// bedrock has closer colors
+		assert_eq!(color_code("#981808", &Palette::bedrock()), Some('m'));
+	}
+
+	#[test]
+	fn leaves_out_decorations_bedrock_uses_for_colors() {
+		let component = json!({"text": "Hi", "underlined": true, "bold": true});
+		assert_eq!(
+			to_legacy(&component, &ComponentStyle::default(), &Palette::java()),
+			"&r&l&nHi"
+		);
+		assert_eq!(
+			to_legacy(&component, &ComponentStyle::default(), &Palette::bedrock()),
+			"&r&lHi"
+		);
+	}
+}
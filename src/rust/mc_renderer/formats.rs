@@ -0,0 +1,342 @@
+use serde_json::{Map, Value, json};
+
+use super::align::{Align, split_alignments};
+use super::component::{COLOR_NAMES, ComponentStyle, to_legacy};
+use super::head::strip_heads;
+use super::palette::Palette;
+use super::{Style, parse_text_with};
+
+/// Formats the renderer's text can be exported in, for pasting into plugins, commands and chat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextFormat {
+	/// `§` codes, as used in plugin configs and raw JSON strings.
+	Section,
+	/// `&` codes, as typed into the renderer and most chat plugins.
+	Ampersand,
+	/// A JSON text component for `/tellraw` and friends.
+	Json,
+	/// Adventure's MiniMessage tags.
+	MiniMessage,
+}
+
+impl TextFormat {
+	pub const ALL: [TextFormat; 4] = [
+		TextFormat::Section,
+		TextFormat::Ampersand,
+		TextFormat::Json,
+		TextFormat::MiniMessage,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			| TextFormat::Section => "Legacy § codes",
+			| TextFormat::Ampersand => "Legacy & codes",
+			| TextFormat::Json => "JSON text component",
+			| TextFormat::MiniMessage => "MiniMessage",
+		}
+	}
+}
+
+/// Converts the renderer's `&` formatted text, with its escapes, into `format`. The JSON and
+/// MiniMessage formats name the colors `palette` gives the codes. Alignment directives and head
+/// placeholders only mean something to the renderer, so they are left out.
+pub fn export(
+	text: &str,
+	format: TextFormat,
+	palette: &Palette,
+) -> String {
+	let (text, _) = split_alignments(&strip_heads(text), Align::Left);
+	match format {
+		| TextFormat::Section => legacy(&text, '§'),
+		| TextFormat::Ampersand => legacy(&text, '&'),
+		| TextFormat::Json => json(&text, palette),
+		| TextFormat::MiniMessage => mini_message(&text, palette),
+	}
+}
+
+/// Turns pasted text into renderer input. JSON text components are converted, anything else is
+/// taken as legacy formatted text with real line breaks, where the `\&` and `\\&` escapes the
+/// `&` export writes stay escapes.
+pub fn import(
+	text: &str,
+	palette: &Palette,
+) -> String {
+	let text = text.trim_end_matches(['\r', '\n']);
+	if let Ok(component @ (Value::Object(_) | Value::Array(_))) = serde_json::from_str(text) {
+		return to_legacy(&component, &ComponentStyle::default(), palette);
+	}
+
+	let mut imported = String::new();
+	let mut chars = text.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			| '\\' if chars.peek() == Some(&'&') => imported.push('\\'),
+			| '\\' if chars.clone().take(2).eq(['\\', '&']) => {
+				chars.next();
+				imported.push_str("\\\\");
+			},
+			| '\\' => imported.push_str("\\\\"),
+			| '\n' => imported.push_str("\\n"),
+			| '\r' => {},
+			| _ => imported.push(c),
+		}
+	}
+	imported
+}
+
+/// Replaces escapes with the characters they stand for and codes with `marker` codes. With `&`
+/// codes a literal `&` stays escaped, and so does a backslash in front of a code, so neither is
+/// read as part of a code again.
+fn legacy(
+	text: &str,
+	marker: char,
+) -> String {
+	let mut converted = String::new();
+	let mut chars = text.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		match c {
+			| '\\' => match chars.next() {
+				| Some('n') => converted.push('\n'),
+				| Some('&') if marker == '&' => converted.push_str("\\&"),
+				| Some(c @ ('&' | '{' | '}')) => converted.push(c),
+				| Some('\\') if marker == '&' && matches!(chars.peek(), Some('&' | '§')) => {
+					converted.push_str("\\\\")
+				},
+				| _ => converted.push('\\'),
+			},
+			| '&' | '§' => converted.push(marker),
+			| _ => converted.push(c),
+		}
+	}
+
+	converted
+}
+
+/// The name of the code `palette` draws `style` with, or its `#rrggbb` value for colors that
+/// have no name, like Bedrock's material colors. White is the default, so it has none.
+fn color_name(
+	style: &Style,
+	palette: &Palette,
+) -> Option<String> {
+	let named = palette
+		.colors()
+		.into_iter()
+		.filter(|(_, color)| *color == style.color)
+		.find_map(|(code, _)| COLOR_NAMES.iter().find(|(_, named)| *named == code));
+	match named {
+		| Some(("white", _)) => None,
+		| Some((name, _)) => Some(name.to_string()),
+		| None => {
+			let [r, g, b] = style.color.0;
+			Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+		},
+	}
+}
+
+/// Consecutive characters sharing a style, with lines joined by `\n`.
+fn runs(
+	text: &str,
+	palette: &Palette,
+) -> Vec<(String, Style)> {
+	let mut runs: Vec<(String, Style)> = Vec::new();
+
+	for (i, line) in parse_text_with(text, palette).iter().enumerate() {
+		// line breaks take the style of what follows them
+		let line_break = line.first().map_or(Style::default(), |(_, style)| *style);
+		let chars = (i > 0).then_some(('\n', line_break));
+
+		for (c, style) in chars.into_iter().chain(line.iter().copied()) {
+			match runs.last_mut() {
+				| Some((run, run_style)) if *run_style == style => run.push(c),
+				| _ => runs.push((c.to_string(), style)),
+			}
+		}
+	}
+
+	runs
+}
+
+fn json(
+	text: &str,
+	palette: &Palette,
+) -> String {
+	let extra: Vec<Value> = runs(text, palette)
+		.into_iter()
+		.map(|(run, style)| {
+			let mut component = Map::new();
+			component.insert("text".to_string(), Value::String(run));
+			if let Some(name) = color_name(&style, palette) {
+				component.insert("color".to_string(), json!(name));
+			}
+			for (enabled, key) in [
+				(style.bold, "bold"),
+				(style.italic, "italic"),
+				(style.underline, "underlined"),
+				(style.strikethrough, "strikethrough"),
+			] {
+				if enabled {
+					component.insert(key.to_string(), Value::Bool(true));
+				}
+			}
+			Value::Object(component)
+		})
+		.collect();
+
+	json!({"text": "", "extra": extra}).to_string()
+}
+
+fn mini_message(
+	text: &str,
+	palette: &Palette,
+) -> String {
+	let mut converted = String::new();
+	let mut current = Style::default();
+
+	for (run, style) in runs(text, palette) {
+		if style != current {
+			// tags can only be added, so anything else starts over from a reset
+			let adds_to_current = style.color == current.color
+				&& (style.bold || !current.bold)
+				&& (style.italic || !current.italic)
+				&& (style.underline || !current.underline)
+				&& (style.strikethrough || !current.strikethrough);
+			if !adds_to_current {
+				if current != Style::default() {
+					converted.push_str("<reset>");
+				}
+				current = Style::default();
+				if let Some(name) = color_name(&style, palette) {
+					converted.push_str(&format!("<{}>", name));
+				}
+			}
+			for (enabled, already, tag) in [
+				(style.bold, current.bold, "<b>"),
+				(style.italic, current.italic, "<i>"),
+				(style.underline, current.underline, "<u>"),
+				(style.strikethrough, current.strikethrough, "<st>"),
+			] {
+				if enabled && !already {
+					converted.push_str(tag);
+				}
+			}
+			current = style;
+		}
+
+		for c in run.chars() {
+			match c {
+				| '\n' => converted.push_str("<newline>"),
+				| '<' | '\\' => {
+					converted.push('\\');
+					converted.push(c);
+				},
+				| _ => converted.push(c),
+			}
+		}
+	}
+
+	converted
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TEXT: &str = "&6Gold &lbold\\n&rplain \\& <tag>";
+
+	#[test]
+	fn exports_legacy_codes() {
+		assert_eq!(
+			export(TEXT, TextFormat::Section, &Palette::java()),
+			"§6Gold §lbold\n§rplain & <tag>"
+		);
+		assert_eq!(
+			export(TEXT, TextFormat::Ampersand, &Palette::java()),
+			"&6Gold &lbold\n&rplain \\& <tag>"
+		);
+	}
+
+	#[test]
+	fn literal_ampersands_survive_a_round_trip() {
+		for text in ["\\&a", "\\\\&agreen", "a\\\\b &l\\&"] {
+			let exported = export(text, TextFormat::Ampersand, &Palette::java());
+			assert_eq!(
+				import(&exported, &Palette::java()),
+				text,
+				"exported as {}",
+				exported
+			);
+		}
+		assert_eq!(
+			export("\\&a", TextFormat::Ampersand, &Palette::java()),
+			"\\&a"
+		);
+		assert_eq!(export("\\&a", TextFormat::Section, &Palette::java()), "&a");
+	}
+
+	#[test]
+	fn exports_json_components() {
+		let exported: Value =
+			serde_json::from_str(&export(TEXT, TextFormat::Json, &Palette::java())).unwrap();
+		assert_eq!(
+			exported,
+			json!({"text": "", "extra": [
+				{"text": "Gold ", "color": "gold"},
+				{"text": "bold", "color": "gold", "bold": true},
+				{"text": "\nplain & <tag>"},
+			]})
+		);
+	}
+
+	#[test]
+	fn exports_mini_message() {
+		assert_eq!(
+			export(TEXT, TextFormat::MiniMessage, &Palette::java()),
+			"<gold>Gold <b>bold<reset><newline>plain & \\<tag>"
+		);
+	}
+
+	#[test]
+	fn exports_colors_of_the_palette() {
+		let bedrock = Palette::bedrock();
+		assert_eq!(
+			export("&aHi &mthere", TextFormat::MiniMessage, &bedrock),
+			"<green>Hi <reset><#971607>there"
+		);
+		let exported: Value =
+			serde_json::from_str(&export("&mHi", TextFormat::Json, &bedrock)).unwrap();
+		assert_eq!(
+			exported,
+			json!({"text": "", "extra": [{"text": "Hi", "color": "#971607"}]})
+		);
+	}
+
+	#[test]
+	fn leaves_out_directives() {
+		let text = "{align:center}&aHi {head:Steve}there\\n{align:right}bye \\{head:Alex}";
+		for format in TextFormat::ALL {
+			let exported = export(text, format, &Palette::java());
+			assert!(!exported.contains("{align:"), "{}", exported);
+			assert!(!exported.contains("{head:Steve}"), "{}", exported);
+			assert!(exported.contains("Hi there"), "{}", exported);
+		}
+		assert_eq!(
+			export(text, TextFormat::Ampersand, &Palette::java()),
+			"&aHi there\nbye {head:Alex}"
+		);
+	}
+
+	#[test]
+	fn imports_pasted_text() {
+		assert_eq!(
+			import("§aHi\\\nthere\n", &Palette::java()),
+			"§aHi\\\\\\nthere"
+		);
+		assert_eq!(
+			import(r#"{"text":"Hi","color":"red"}"#, &Palette::java()),
+			"&r&cHi"
+		);
+		// a plain number is valid JSON but not a component
+		assert_eq!(import("42", &Palette::java()), "42");
+	}
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use image::{Pixel, Rgb, RgbImage, Rgba, RgbaImage, imageops};
+use rusttype::{Font, Scale};
+
+use super::{Style, advance, find_unescaped};
+
+/// First of the private use characters that stand in for inline heads while text is laid out.
+const FIRST_HEAD_CHAR: u32 = 0xe000;
+const HEAD_CHAR_COUNT: u32 = 0x1000;
+
+/// Font pixels an inline head takes, 8 for the head and one of space after it like a glyph.
+pub const HEAD_ADVANCE: f32 = 9.0;
+
+pub fn is_head_char(c: char) -> bool { head_index(c).is_some() }
+
+pub fn head_index(c: char) -> Option<usize> {
+	let offset = (c as u32).checked_sub(FIRST_HEAD_CHAR)?;
+	(offset < HEAD_CHAR_COUNT).then_some(offset as usize)
+}
+
+/// Names used in `{head:Name}` placeholders in `text`, in order.
+pub fn head_names(text: &str) -> Vec<String> {
+	let mut names = Vec::new();
+	let mut rest = text;
+	while let Some(start) = find_unescaped(rest, "{head:") {
+		rest = &rest[start + "{head:".len()..];
+		if let Some((name, _)) = rest.split_once('}') {
+			names.push(name.to_string());
+		}
+	}
+	names
+}
+
+/// Removes the `{head:Name}` placeholders from `text`, for output that can't show heads.
+pub fn strip_heads(text: &str) -> String {
+	let mut stripped = String::new();
+	let mut rest = text;
+	while let Some(start) = find_unescaped(rest, "{head:") {
+		let Some((_, remaining)) = rest[start + "{head:".len()..].split_once('}') else {
+			break;
+		};
+		stripped.push_str(&rest[..start]);
+		rest = remaining;
+	}
+	stripped.push_str(rest);
+	stripped
+}
+
+/// Replaces each `{head:Name}` with a character standing in for the head `lookup` returns for
+/// that name, returning the heads in the order of their characters. Placeholders `lookup` has
+/// no head for are left as typed.
+pub fn inline_heads(
+	text: &str,
+	mut lookup: impl FnMut(&str) -> Option<RgbaImage>,
+) -> (String, Vec<RgbaImage>) {
+	let mut replaced = String::new();
+	let mut heads = Vec::new();
+	let mut names: HashMap<String, char> = HashMap::new();
+	let mut rest = text;
+
+	while let Some(start) = find_unescaped(rest, "{head:") {
+		replaced.push_str(&rest[..start]);
+		let after = &rest[start + "{head:".len()..];
+		let Some((name, remaining)) = after.split_once('}') else {
+			break;
+		};
+
+		let c = match names.get(name) {
+			| Some(c) => Some(*c),
+			| None if heads.len() < HEAD_CHAR_COUNT as usize => lookup(name).map(|head| {
+				let c = char::from_u32(FIRST_HEAD_CHAR + heads.len() as u32).unwrap();
+				heads.push(head);
+				names.insert(name.to_string(), c);
+				c
+			}),
+			| None => None,
+		};
+		match c {
+			| Some(c) => replaced.push(c),
+			| None => replaced.push_str(&rest[start..rest.len() - remaining.len()]),
+		}
+		rest = remaining;
+	}
+
+	replaced.push_str(rest);
+	(replaced, heads)
+}
+
+/// Draws the heads standing in a line that was drawn with its baseline at `origin`, filling the
+/// space the line left for them. Heads sit on the baseline and are as tall as the font.
+pub(super) fn draw_inline_heads(
+	image: &mut RgbImage,
+	line: &[(char, Style)],
+	heads: &[RgbaImage],
+	fonts: &HashMap<&str, Font>,
+	scale: Scale,
+	origin: (f32, f32),
+) {
+	let unit = scale.y / 8.0;
+	let mut x = origin.0;
+
+	for &(c, style) in line {
+		if let Some(head) = head_index(c).and_then(|index| heads.get(index)) {
+			draw_head(
+				image,
+				head,
+				(x.round() as i64, (origin.1 - 7.0 * unit).round() as i64),
+				(8.0 * unit).round() as u32,
+			);
+		}
+		x += advance(c, style, fonts, scale);
+	}
+}
+
+/// Reads the face of a skin PNG with its hat layer on top, as an 8x8 image.
+pub fn load_head(path: &Path) -> Result<RgbaImage, String> {
+	let skin = image::open(path)
+		.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+		.to_rgba8();
+	head_from_skin(&skin)
+}
+
+/// Accepts both 64x64 skins and the older 64x32 ones, which have the hat in the same place.
+pub fn head_from_skin(skin: &RgbaImage) -> Result<RgbaImage, String> {
+	if skin.width() != 64 || (skin.height() != 64 && skin.height() != 32) {
+		return Err(format!(
+			"Skins are 64x64 or 64x32 pixels, not {}x{}",
+			skin.width(),
+			skin.height()
+		));
+	}
+
+	let mut head = imageops::crop_imm(skin, 8, 8, 8, 8).to_image();
+	for pixel in head.pixels_mut() {
+		// the face layer has no transparency in game
+		pixel[3] = 255;
+	}
+	let hat = imageops::crop_imm(skin, 40, 8, 8, 8).to_image();
+	imageops::overlay(&mut head, &hat, 0, 0);
+	Ok(head)
+}
+
+/// Draws `head` scaled to `size` pixels with nearest-neighbour filtering, keeping it pixelated.
+pub fn draw_head(
+	image: &mut RgbImage,
+	head: &RgbaImage,
+	(left, top): (i64, i64),
+	size: u32,
+) {
+	let size = size.max(1);
+	for y in 0..size {
+		for x in 0..size {
+			let (px, py) = (left + x as i64, top + y as i64);
+			if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+				continue;
+			}
+
+			let Rgba([r, g, b, a]) =
+				*head.get_pixel(x * head.width() / size, y * head.height() / size);
+			let alpha = a as f32 / 255.0;
+			let pixel = image.get_pixel_mut(px as u32, py as u32);
+			*pixel = Rgb([r, g, b]).map2(pixel, |top, bottom| {
+				(top as f32 * alpha + bottom as f32 * (1.0 - alpha)) as u8
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn skin() -> RgbaImage {
+		let mut skin = RgbaImage::new(64, 64);
+		for y in 8..16 {
+			for x in 8..16 {
+				skin.put_pixel(x, y, Rgba([200, 150, 100, 0]));
+			}
+		}
+		// one hat pixel over the face's top left corner
+		skin.put_pixel(40, 8, Rgba([0, 0, 255, 255]));
+		skin
+	}
+
+	#[test]
+	fn layers_hat_over_face() {
+		let head = head_from_skin(&skin()).unwrap();
+		assert_eq!(head.dimensions(), (8, 8));
+		assert_eq!(head.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+		assert_eq!(head.get_pixel(1, 0), &Rgba([200, 150, 100, 255]));
+	}
+
+	#[test]
+	fn rejects_other_sizes() {
+		assert!(head_from_skin(&RgbaImage::new(32, 32)).is_err());
+		assert!(head_from_skin(&RgbaImage::new(64, 32)).is_ok());
+	}
+
+	#[test]
+	fn replaces_known_placeholders() {
+		let (text, heads) = inline_heads("{head:Steve} hi {head:Alex}{head:Steve}", |name| {
+			(name == "Steve").then(|| RgbaImage::new(8, 8))
+		});
+		assert_eq!(text, "\u{e000} hi {head:Alex}\u{e000}");
+		assert_eq!(heads.len(), 1);
+		assert_eq!(
+			head_names("{head:Steve} and {head:Alex"),
+			vec!["Steve".to_string()]
+		);
+
+		// escaped placeholders stay text
+		let (text, heads) = inline_heads("\\{head:Steve}", |_| Some(RgbaImage::new(8, 8)));
+		assert_eq!(text, "\\{head:Steve}");
+		assert!(heads.is_empty());
+		assert!(head_names("\\{head:Steve}").is_empty());
+	}
+
+	#[test]
+	fn scales_without_smoothing() {
+		let head = head_from_skin(&skin()).unwrap();
+		let mut image = RgbImage::new(20, 20);
+		draw_head(&mut image, &head, (2, 2), 16);
+
+		// each skin pixel becomes a 2x2 block
+		for (x, y) in [(2, 2), (3, 3)] {
+			assert_eq!(image.get_pixel(x, y), &Rgb([0, 0, 255]));
+		}
+		assert_eq!(image.get_pixel(4, 2), &Rgb([200, 150, 100]));
+		assert_eq!(image.get_pixel(1, 1).channels(), &[0, 0, 0]);
+	}
+}
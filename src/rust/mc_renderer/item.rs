@@ -0,0 +1,698 @@
+use serde_json::Value;
+
+use super::component::{ComponentStyle, to_legacy};
+use super::palette::Palette;
+use super::snbt::{Reader, Tag};
+use super::{
+	Layout,
+	TextOptions,
+	format_number,
+	load_background,
+	load_fonts,
+	prompt_palette,
+	prompt_save,
+	render,
+};
+use crate::main;
+use crate::stuff::input;
+
+// vanilla's order for attribute modifier groups
+const SLOTS: [&str; 6] = ["mainhand", "offhand", "feet", "legs", "chest", "head"];
+
+/// The attack damage and speed vanilla shows for weapons and tools without custom modifiers.
+const DEFAULT_WEAPON_STATS: &[(&str, f64, f64)] = &[
+	("wooden_sword", 4.0, 1.6),
+	("stone_sword", 5.0, 1.6),
+	("iron_sword", 6.0, 1.6),
+	("golden_sword", 4.0, 1.6),
+	("diamond_sword", 7.0, 1.6),
+	("netherite_sword", 8.0, 1.6),
+	("wooden_axe", 7.0, 0.8),
+	("stone_axe", 9.0, 0.8),
+	("iron_axe", 9.0, 0.9),
+	("golden_axe", 7.0, 1.0),
+	("diamond_axe", 9.0, 1.0),
+	("netherite_axe", 10.0, 1.0),
+	("wooden_pickaxe", 2.0, 1.2),
+	("stone_pickaxe", 3.0, 1.2),
+	("iron_pickaxe", 4.0, 1.2),
+	("golden_pickaxe", 2.0, 1.2),
+	("diamond_pickaxe", 5.0, 1.2),
+	("netherite_pickaxe", 6.0, 1.2),
+	("wooden_shovel", 2.5, 1.0),
+	("stone_shovel", 3.5, 1.0),
+	("iron_shovel", 4.5, 1.0),
+	("golden_shovel", 2.5, 1.0),
+	("diamond_shovel", 5.5, 1.0),
+	("netherite_shovel", 6.5, 1.0),
+	("wooden_hoe", 1.0, 1.0),
+	("stone_hoe", 1.0, 2.0),
+	("iron_hoe", 1.0, 3.0),
+	("golden_hoe", 1.0, 1.0),
+	("diamond_hoe", 1.0, 4.0),
+	("netherite_hoe", 1.0, 4.0),
+	("trident", 9.0, 1.1),
+	("mace", 6.0, 0.6),
+];
+
+/// Armor, toughness and knockback resistance per material, then armor points per piece.
+const DEFAULT_ARMOR: &[(&str, f64, f64, [f64; 4])] = &[
+	("leather", 0.0, 0.0, [1.0, 3.0, 2.0, 1.0]),
+	("chainmail", 0.0, 0.0, [2.0, 5.0, 4.0, 1.0]),
+	("iron", 0.0, 0.0, [2.0, 6.0, 5.0, 2.0]),
+	("golden", 0.0, 0.0, [2.0, 5.0, 3.0, 1.0]),
+	("diamond", 2.0, 0.0, [3.0, 8.0, 6.0, 3.0]),
+	("netherite", 3.0, 0.1, [3.0, 8.0, 6.0, 3.0]),
+];
+
+const ARMOR_PIECES: [(&str, &str); 4] = [
+	("helmet", "head"),
+	("chestplate", "chest"),
+	("leggings", "legs"),
+	("boots", "feet"),
+];
+
+const UNCOMMON: &[&str] = &[
+	"experience_bottle",
+	"dragon_breath",
+	"elytra",
+	"enchanted_book",
+	"creeper_head",
+	"dragon_head",
+	"piglin_head",
+	"player_head",
+	"skeleton_skull",
+	"wither_skeleton_skull",
+	"zombie_head",
+	"heart_of_the_sea",
+	"nether_star",
+	"totem_of_undying",
+];
+const RARE: &[&str] = &["beacon", "conduit", "end_crystal", "golden_apple"];
+const EPIC: &[&str] = &[
+	"enchanted_golden_apple",
+	"dragon_egg",
+	"command_block",
+	"chain_command_block",
+	"repeating_command_block",
+	"command_block_minecart",
+	"structure_block",
+	"structure_void",
+	"jigsaw",
+	"barrier",
+	"light",
+	"debug_stick",
+	"heavy_core",
+	"mace",
+];
+
+const SINGLE_LEVEL_ENCHANTMENTS: &[&str] = &[
+	"aqua_affinity",
+	"binding_curse",
+	"channeling",
+	"flame",
+	"infinity",
+	"mending",
+	"multishot",
+	"silk_touch",
+	"vanishing_curse",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+struct Modifier {
+	attribute: String,
+	amount:    f64,
+	/// 0 adds the amount, 1 and 2 multiply and are shown as percentages
+	operation: u8,
+	slot:      Option<String>,
+}
+
+/// What a `/give` command's NBT or components say the tooltip should show.
+#[derive(Debug, Default)]
+pub struct Item {
+	id: String,
+	name: Option<Value>,
+	item_name: Option<Value>,
+	lore: Vec<Value>,
+	enchantments: Vec<(String, u32)>,
+	modifiers: Vec<Modifier>,
+	unbreakable: bool,
+	hide_tooltip: bool,
+	hide_enchantments: bool,
+	hide_modifiers: bool,
+	hide_unbreakable: bool,
+}
+
+fn strip_namespace(id: &str) -> &str { id.strip_prefix("minecraft:").unwrap_or(id) }
+
+/// Turns an id like `fire_aspect` into `Fire Aspect`, keeping small words lowercase.
+fn title_case(id: &str) -> String {
+	id.split('_')
+		.enumerate()
+		.map(|(i, word)| match word {
+			| "of" | "the" | "on" | "a" if i > 0 => word.to_string(),
+			| _ => {
+				let mut chars = word.chars();
+				chars
+					.next()
+					.map(|first| first.to_uppercase().chain(chars).collect())
+					.unwrap_or_default()
+			},
+		})
+		.collect::<Vec<String>>()
+		.join(" ")
+}
+
+/// Text components are JSON strings before 1.21.5 and SNBT after, plain strings are both.
+fn component(tag: &Tag) -> Value {
+	match tag {
+		| Tag::String(text) => {
+			serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.clone()))
+		},
+		| _ => tag.to_json(),
+	}
+}
+
+fn roman(level: u32) -> String {
+	const NUMERALS: [&str; 10] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+	match level {
+		| 1..=10 => NUMERALS[level as usize - 1].to_string(),
+		// vanilla only has translations up to 10
+		| _ => format!("enchantment.level.{}", level),
+	}
+}
+
+fn enchantment_name(id: &str) -> String {
+	match strip_namespace(id) {
+		| "binding_curse" => "Curse of Binding".to_string(),
+		| "vanishing_curse" => "Curse of Vanishing".to_string(),
+		| "sweeping" | "sweeping_edge" => "Sweeping Edge".to_string(),
+		| id => title_case(id),
+	}
+}
+
+fn attribute_name(id: &str) -> String {
+	let id = strip_namespace(id);
+	let id = id.rsplit('.').next().unwrap_or(id);
+	match id {
+		| "movement_speed" => "Speed".to_string(),
+		| id => title_case(id),
+	}
+}
+
+fn slot_heading(slot: &str) -> &'static str {
+	match slot {
+		| "mainhand" => "When in Main Hand:",
+		| "offhand" => "When in Off Hand:",
+		| "head" => "When on Head:",
+		| "chest" => "When on Body:",
+		| "legs" => "When on Legs:",
+		| "feet" => "When on Feet:",
+		| "hand" => "When in Hand:",
+		| "armor" => "When worn:",
+		| _ => "When equipped:",
+	}
+}
+
+/// Formats like vanilla's `#.##` pattern.
+impl Item {
+	fn new(id: &str) -> Self {
+		Item {
+			id: strip_namespace(id).to_string(),
+			..Default::default()
+		}
+	}
+
+	/// Reads the pre 1.20.5 `{display:{...},Enchantments:[...]}` item NBT.
+	fn apply_nbt(
+		&mut self,
+		tag: &Tag,
+	) {
+		if let Some(display) = tag.get("display") {
+			self.name = display.get("Name").map(component);
+			if let Some(lore) = display.get("Lore").and_then(Tag::as_list) {
+				self.lore = lore.iter().map(component).collect();
+			}
+		}
+
+		for key in ["Enchantments", "StoredEnchantments"] {
+			for enchantment in tag.get(key).and_then(Tag::as_list).unwrap_or_default() {
+				let id = enchantment.get("id").and_then(Tag::as_str);
+				let level = enchantment.get("lvl").and_then(Tag::as_f64);
+				if let (Some(id), Some(level)) = (id, level) {
+					self.enchantments.push((id.to_string(), level as u32));
+				}
+			}
+		}
+
+		for modifier in tag
+			.get("AttributeModifiers")
+			.and_then(Tag::as_list)
+			.unwrap_or_default()
+		{
+			let attribute = modifier.get("AttributeName").and_then(Tag::as_str);
+			let amount = modifier.get("Amount").and_then(Tag::as_f64);
+			if let (Some(attribute), Some(amount)) = (attribute, amount) {
+				self.modifiers.push(Modifier {
+					attribute: attribute.to_string(),
+					amount,
+					operation: modifier
+						.get("Operation")
+						.and_then(Tag::as_f64)
+						.unwrap_or(0.0) as u8,
+					slot: modifier
+						.get("Slot")
+						.and_then(Tag::as_str)
+						.map(str::to_string),
+				});
+			}
+		}
+
+		self.unbreakable = tag
+			.get("Unbreakable")
+			.and_then(Tag::as_bool)
+			.unwrap_or(false);
+
+		let hide_flags = tag.get("HideFlags").and_then(Tag::as_f64).unwrap_or(0.0) as u32;
+		self.hide_enchantments = hide_flags & 1 != 0;
+		self.hide_modifiers = hide_flags & 2 != 0;
+		self.hide_unbreakable = hide_flags & 4 != 0;
+	}
+
+	/// Reads one entry of the 1.20.5+ `[custom_name=...,lore=[...]]` component syntax.
+	fn apply_component(
+		&mut self,
+		key: &str,
+		tag: &Tag,
+	) {
+		let hidden = tag.get("show_in_tooltip").and_then(Tag::as_bool) == Some(false);
+
+		match strip_namespace(key) {
+			| "custom_name" => self.name = Some(component(tag)),
+			| "item_name" => self.item_name = Some(component(tag)),
+			| "lore" => {
+				self.lore = tag
+					.as_list()
+					.unwrap_or_default()
+					.iter()
+					.map(component)
+					.collect()
+			},
+			| "enchantments" | "stored_enchantments" => {
+				// 1.21.5 dropped the `levels` wrapper
+				let levels = tag.get("levels").unwrap_or(tag);
+				for (id, level) in levels.as_compound().unwrap_or_default() {
+					if let Some(level) = level.as_f64().filter(|_| id != "show_in_tooltip") {
+						self.enchantments.push((id.clone(), level as u32));
+					}
+				}
+				self.hide_enchantments |= hidden;
+			},
+			| "attribute_modifiers" => {
+				let modifiers = tag.get("modifiers").unwrap_or(tag);
+				for modifier in modifiers.as_list().unwrap_or_default() {
+					let attribute = modifier.get("type").and_then(Tag::as_str);
+					let amount = modifier.get("amount").and_then(Tag::as_f64);
+					let operation = match modifier.get("operation").and_then(Tag::as_str) {
+						| Some("add_multiplied_base") => 1,
+						| Some("add_multiplied_total") => 2,
+						| _ => 0,
+					};
+					if let (Some(attribute), Some(amount)) = (attribute, amount) {
+						self.modifiers.push(Modifier {
+							attribute: attribute.to_string(),
+							amount,
+							operation,
+							slot: Some(
+								modifier
+									.get("slot")
+									.and_then(Tag::as_str)
+									.unwrap_or("any")
+									.to_string(),
+							),
+						});
+					}
+				}
+				self.hide_modifiers |= hidden;
+			},
+			| "unbreakable" => {
+				self.unbreakable = true;
+				self.hide_unbreakable |= hidden;
+			},
+			| "hide_tooltip" => self.hide_tooltip = true,
+			| "tooltip_display" => {
+				self.hide_tooltip |= tag.get("hide_tooltip").and_then(Tag::as_bool) == Some(true);
+				for hidden in tag
+					.get("hidden_components")
+					.and_then(Tag::as_list)
+					.unwrap_or_default()
+				{
+					match hidden.as_str().map(strip_namespace) {
+						| Some("enchantments" | "stored_enchantments") => {
+							self.hide_enchantments = true
+						},
+						| Some("attribute_modifiers") => self.hide_modifiers = true,
+						| Some("unbreakable") => self.hide_unbreakable = true,
+						| _ => {},
+					}
+				}
+			},
+			| _ => {},
+		}
+	}
+
+	/// The color code of the item's rarity, raised a tier when it is enchanted.
+	fn rarity_color(&self) -> char {
+		let tier = if EPIC.contains(&self.id.as_str()) {
+			3
+		} else if RARE.contains(&self.id.as_str()) || self.id.starts_with("music_disc_") {
+			2
+		} else if UNCOMMON.contains(&self.id.as_str()) {
+			1
+		} else {
+			0
+		};
+		let tier = if self.enchantments.is_empty() || self.id == "enchanted_book" {
+			tier
+		} else {
+			(tier + 1).clamp(2, 3)
+		};
+
+		['f', 'e', 'b', 'd'][tier]
+	}
+
+	/// Vanilla's attribute lines for the item when no custom modifiers replace them.
+	fn default_modifier_lines(&self) -> Vec<(String, Vec<String>)> {
+		if let Some((_, damage, speed)) =
+			DEFAULT_WEAPON_STATS.iter().find(|(id, ..)| *id == self.id)
+		{
+			return vec![(
+				"mainhand".to_string(),
+				vec![
+					format!("&2 {} Attack Damage", format_number(*damage)),
+					format!("&2 {} Attack Speed", format_number(*speed)),
+				],
+			)];
+		}
+
+		let armor = self.id.split_once('_').and_then(|(material, piece)| {
+			let (_, toughness, knockback, points) =
+				DEFAULT_ARMOR.iter().find(|(name, ..)| *name == material)?;
+			let index = ARMOR_PIECES.iter().position(|(name, _)| *name == piece)?;
+			Some((ARMOR_PIECES[index].1, points[index], *toughness, *knockback))
+		});
+		let armor = armor.or((self.id == "turtle_helmet").then_some(("head", 2.0, 0.0, 0.0)));
+
+		match armor {
+			| Some((slot, points, toughness, knockback)) => {
+				let mut lines = vec![format!("&9+{} Armor", format_number(points))];
+				if toughness > 0.0 {
+					lines.push(format!("&9+{} Armor Toughness", format_number(toughness)));
+				}
+				if knockback > 0.0 {
+					lines.push(format!(
+						"&9+{} Knockback Resistance",
+						format_number(knockback * 10.0)
+					));
+				}
+				vec![(slot.to_string(), lines)]
+			},
+			| None => Vec::new(),
+		}
+	}
+
+	fn modifier_lines(&self) -> Vec<(String, Vec<String>)> {
+		if self.modifiers.is_empty() {
+			return self.default_modifier_lines();
+		}
+
+		// modifiers without a slot apply to every slot, and are listed under each of them
+		let mut slots: Vec<String> = SLOTS.iter().map(|slot| slot.to_string()).collect();
+		for modifier in &self.modifiers {
+			if let Some(slot) = &modifier.slot {
+				if !slots.contains(slot) {
+					slots.push(slot.clone());
+				}
+			}
+		}
+
+		slots
+			.into_iter()
+			.map(|slot| {
+				let lines = self
+					.modifiers
+					.iter()
+					.filter(|modifier| modifier.slot.as_ref().is_none_or(|s| *s == slot))
+					.map(|modifier| {
+						let mut amount = modifier.amount;
+						if modifier.operation != 0 {
+							amount *= 100.0;
+						} else if attribute_name(&modifier.attribute) == "Knockback Resistance" {
+							amount *= 10.0;
+						}
+						let percent = if modifier.operation != 0 { "%" } else { "" };
+						let (color, sign) = if amount < 0.0 { ('c', '-') } else { ('9', '+') };
+						format!(
+							"&{}{}{}{} {}",
+							color,
+							sign,
+							format_number(amount.abs()),
+							percent,
+							attribute_name(&modifier.attribute)
+						)
+					})
+					.collect::<Vec<_>>();
+				(slot, lines)
+			})
+			.filter(|(_, lines)| !lines.is_empty())
+			.collect()
+	}
+
+	/// The tooltip as `&` formatted lines, in the order the client shows them.
+	pub fn tooltip_lines(
+		&self,
+		palette: &Palette,
+	) -> Vec<String> {
+		if self.hide_tooltip {
+			return Vec::new();
+		}
+
+		let rarity = ComponentStyle {
+			color: Some(self.rarity_color()).filter(|color| *color != 'f'),
+			..Default::default()
+		};
+		let name = match (&self.name, &self.item_name) {
+			| (Some(name), _) => to_legacy(
+				name,
+				&ComponentStyle {
+					italic: true,
+					..rarity
+				},
+				palette,
+			),
+			| (None, Some(name)) => to_legacy(name, &rarity, palette),
+			| (None, None) => to_legacy(&Value::String(title_case(&self.id)), &rarity, palette),
+		};
+		let mut lines = vec![name];
+
+		if !self.hide_enchantments {
+			for (id, level) in &self.enchantments {
+				let color = if id.ends_with("_curse") { 'c' } else { '7' };
+				let mut line = format!("&{}{}", color, enchantment_name(id));
+				if *level != 1 || !SINGLE_LEVEL_ENCHANTMENTS.contains(&strip_namespace(id)) {
+					line.push(' ');
+					line.push_str(&roman(*level));
+				}
+				lines.push(line);
+			}
+		}
+
+		let lore_style = ComponentStyle {
+			color: Some('5'),
+			italic: true,
+			..Default::default()
+		};
+		lines.extend(
+			self.lore
+				.iter()
+				.map(|line| to_legacy(line, &lore_style, palette)),
+		);
+
+		if !self.hide_modifiers {
+			for (slot, modifier_lines) in self.modifier_lines() {
+				lines.push(String::new());
+				lines.push(format!("&7{}", slot_heading(&slot)));
+				lines.extend(modifier_lines);
+			}
+		}
+
+		if self.unbreakable && !self.hide_unbreakable {
+			lines.push("&9Unbreakable".to_string());
+		}
+
+		lines
+	}
+}
+
+/// Parses `/give <target> <item>[components]{nbt} [count]` in either the pre or post 1.20.5
+/// syntax.
+pub fn parse_give(command: &str) -> Result<Item, String> {
+	let mut reader = Reader::new(command.trim().trim_start_matches('/'));
+
+	if reader.read_until(&[]) != "give" {
+		return Err("Expected a /give command".to_string());
+	}
+	reader.skip_whitespace();
+	if reader.read_until(&['[']).is_empty() {
+		return Err("Expected a target".to_string());
+	}
+	// selector arguments aren't SNBT, so they are skipped rather than parsed
+	reader.skip_brackets();
+	reader.skip_whitespace();
+
+	let id = reader.read_until(&['[', '{']);
+	if id.is_empty() {
+		return Err("Expected an item".to_string());
+	}
+	let mut item = Item::new(&id);
+
+	if reader.peek() == Some('[') {
+		reader.expect('[')?;
+		while !reader.accept(']') {
+			if reader.accept('!') {
+				reader.read_key(',')?;
+			} else {
+				let key = reader.read_key('=')?;
+				reader.expect('=')?;
+				item.apply_component(&key, &reader.read_tag()?);
+			}
+
+			if !reader.accept(',') && reader.peek() != Some(']') {
+				return Err(format!("Expected ',' or ']' before '{}'", reader.rest()));
+			}
+		}
+	}
+
+	if reader.peek() == Some('{') {
+		item.apply_nbt(&reader.read_tag()?);
+	}
+
+	Ok(item)
+}
+
+pub fn start() {
+	let command = input("\nPaste a /give command:", true);
+
+	let item = match parse_give(&command) {
+		| Ok(item) => item,
+		| Err(e) => {
+			println!("\x1b[31mCouldn't read the command:\x1b[0m {}", e);
+			return main();
+		},
+	};
+
+	let palette = prompt_palette();
+	let lines = item.tooltip_lines(&palette);
+	if lines.is_empty() {
+		println!("\nThis item's tooltip is hidden");
+		return main();
+	}
+
+	println!();
+	for line in &lines {
+		println!("{}", line);
+	}
+
+	let image = render(
+		&lines.join("\\n"),
+		&load_fonts(),
+		&load_background(),
+		Layout::Tooltip,
+		2,
+		&TextOptions::vanilla(&palette, Layout::Tooltip),
+	);
+
+	prompt_save(&image);
+
+	main()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reads_legacy_nbt() {
+		let item = parse_give(
+			r#"/give @p minecraft:diamond_sword{display:{Name:'{"text":"Excalibur","color":"gold"}',Lore:['"Forged in fire"']},Enchantments:[{id:"minecraft:sharpness",lvl:5s},{id:"minecraft:mending",lvl:1s}],Unbreakable:1b} 1"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			item.tooltip_lines(&Palette::java()),
+			vec![
+				"&r&6&oExcalibur",
+				"&7Sharpness V",
+				"&7Mending",
+				"&r&5&oForged in fire",
+				"",
+				"&7When in Main Hand:",
+				"&2 7 Attack Damage",
+				"&2 1.6 Attack Speed",
+				"&9Unbreakable",
+			]
+		);
+	}
+
+	#[test]
+	fn reads_item_components() {
+		let item = parse_give(
+			r#"give @a[name="Steve",limit=1] netherite_helmet[custom_name='{"text":"Crown","italic":false}',enchantments={levels:{"minecraft:protection":4,"minecraft:binding_curse":1}},attribute_modifiers=[{type:"minecraft:generic.max_health",amount:4,operation:"add_value",slot:"head",id:"crown"},{type:"movement_speed",amount:-0.1,operation:"add_multiplied_base",slot:"head",id:"slow"}]]"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			item.tooltip_lines(&Palette::java()),
+			vec![
+				"&r&bCrown",
+				"&7Protection IV",
+				"&cCurse of Binding",
+				"",
+				"&7When on Head:",
+				"&9+4 Max Health",
+				"&c-10% Speed",
+			]
+		);
+	}
+
+	#[test]
+	fn respects_hidden_parts() {
+		let legacy = parse_give(
+			"/give @p iron_axe{Enchantments:[{id:efficiency,lvl:3}],HideFlags:3,Unbreakable:1b}",
+		)
+		.unwrap();
+		assert_eq!(
+			legacy.tooltip_lines(&Palette::java()),
+			vec!["&r&bIron Axe", "&9Unbreakable"]
+		);
+
+		let components = parse_give("/give @p stick[hide_tooltip={}]").unwrap();
+		assert!(components.tooltip_lines(&Palette::java()).is_empty());
+
+		let display = parse_give(
+			"/give @p diamond_chestplate[tooltip_display={hidden_components:[\"attribute_modifiers\"]}]",
+		)
+		.unwrap();
+		assert_eq!(
+			display.tooltip_lines(&Palette::java()),
+			vec!["Diamond Chestplate"]
+		);
+	}
+
+	#[test]
+	fn rejects_other_commands() {
+		assert!(parse_give("/tp @p 0 0 0").is_err());
+		assert!(parse_give("/give @p").is_err());
+		assert!(parse_give("/give @p stone[custom_name='a'").is_err());
+	}
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use rusttype::{Font, Scale};
+
+use super::formats::{TextFormat, export};
+use super::palette::Palette;
+use super::{Style, advance, load_fonts, parse_text_with, prompt_palette};
+use crate::main;
+use crate::stuff::{input, menu};
+
+/// Places a message can end up in, each with the widest line it shows without wrapping or
+/// cutting text off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+	/// The chat box at its default width setting.
+	Chat,
+	/// One line of a sign's text.
+	Sign,
+	/// A line of the sidebar. The sidebar grows to fit its widest line rather than cutting it
+	/// off, so this is the width where it starts covering too much of a small window.
+	Scoreboard,
+}
+
+impl Limit {
+	pub const ALL: [Limit; 3] = [Limit::Chat, Limit::Sign, Limit::Scoreboard];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			| Limit::Chat => "Chat",
+			| Limit::Sign => "Sign",
+			| Limit::Scoreboard => "Scoreboard",
+		}
+	}
+
+	/// Width in GUI pixels.
+	pub fn width(&self) -> u32 {
+		match self {
+			| Limit::Chat => 320,
+			| Limit::Sign => 90,
+			| Limit::Scoreboard => 120,
+		}
+	}
+}
+
+/// Widths of a rendered line in GUI pixels, one font pixel each.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineWidth {
+	pub width:       u32,
+	/// Width of a space in the formatting the line starts with, what padding it would add.
+	pub space_width: f32,
+}
+
+impl LineWidth {
+	pub fn fits(
+		&self,
+		limit: Limit,
+	) -> bool {
+		self.width <= limit.width()
+	}
+
+	/// Number of spaces that center the line in `limit`, none if it doesn't fit.
+	pub fn center_padding(
+		&self,
+		limit: Limit,
+	) -> usize {
+		let free = limit.width().saturating_sub(self.width);
+		(free as f32 / 2.0 / self.space_width.max(1.0)) as usize
+	}
+}
+
+/// Measures every line of `text` with the same advance widths the renderer draws with.
+pub fn measure(
+	text: &str,
+	fonts: &HashMap<&str, Font>,
+	palette: &Palette,
+) -> Vec<LineWidth> {
+	let scale = Scale::uniform(8.0);
+
+	// formatting carries over from the line before, so the lines are parsed together
+	parse_text_with(text, palette)
+		.iter()
+		.map(|line| {
+			let width: f32 = line
+				.iter()
+				.map(|&(c, style)| advance(c, style, fonts, scale))
+				.sum();
+			let first_style = line.first().map_or(Style::default(), |(_, style)| *style);
+			LineWidth {
+				width:       width.ceil() as u32,
+				space_width: advance(' ', first_style, fonts, scale),
+			}
+		})
+		.collect()
+}
+
+/// Splits `text` on its `\n` escapes, keeping every other escape as typed.
+pub fn split_lines(text: &str) -> Vec<String> {
+	let mut lines = vec![String::new()];
+	let mut chars = text.chars();
+
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			lines.last_mut().unwrap().push(c);
+			continue;
+		}
+		match chars.next() {
+			| Some('n') => lines.push(String::new()),
+			| Some(escaped) => {
+				let line = lines.last_mut().unwrap();
+				line.push(c);
+				line.push(escaped);
+			},
+			| None => lines.last_mut().unwrap().push(c),
+		}
+	}
+
+	lines
+}
+
+pub fn start() {
+	let text = input(
+		"\nEnter the message to measure, using & codes and \\n for new lines:",
+		true,
+	);
+
+	println!("\nWhich limit should lines be checked against?");
+	let options: Vec<String> = Limit::ALL
+		.iter()
+		.map(|limit| format!("{} ({}px)", limit.name(), limit.width()))
+		.collect();
+	let limit = Limit::ALL[menu(options.iter().map(String::as_str).collect()) as usize - 1];
+	let palette = prompt_palette();
+
+	let widths = measure(&text, &load_fonts(), &palette);
+	let lines = split_lines(&text);
+
+	println!();
+	for (i, (line, width)) in lines.iter().zip(&widths).enumerate() {
+		if width.fits(limit) {
+			println!(
+				"Line {}: {}px \x1b[32mfits\x1b[0m ({}px left)",
+				i + 1,
+				width.width,
+				limit.width() - width.width
+			);
+		} else {
+			println!(
+				"Line {}: {}px \x1b[31mtoo wide by {}px\x1b[0m",
+				i + 1,
+				width.width,
+				width.width - limit.width()
+			);
+		}
+		println!("\t{}", line);
+	}
+
+	if limit == Limit::Chat {
+		println!("\nCentered for /tellraw:");
+		let centered: Vec<String> = lines
+			.iter()
+			.zip(&widths)
+			.map(|(line, width)| format!("{}{}", " ".repeat(width.center_padding(limit)), line))
+			.collect();
+		let exported = export(&centered.join("\\n"), TextFormat::Section, &palette);
+		let json = serde_json::to_string(&exported).unwrap();
+		println!("/tellraw @a {}", json);
+	}
+
+	main()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn measures_each_line() {
+		let fonts = load_fonts();
+		let widths = measure("ii\\n&lii", &fonts, &Palette::java());
+		assert_eq!(widths.len(), 2);
+		assert_eq!(widths[0].width, 4);
+		// bold glyphs are a pixel wider
+		assert!(widths[1].width > widths[0].width);
+		assert!(widths[0].space_width > 0.0);
+	}
+
+	#[test]
+	fn checks_limits() {
+		let line = LineWidth {
+			width:       100,
+			space_width: 4.0,
+		};
+		assert!(line.fits(Limit::Chat));
+		assert!(!line.fits(Limit::Sign));
+		// (320 - 100) / 2 = 110 pixels of padding
+		assert_eq!(line.center_padding(Limit::Chat), 27);
+		assert_eq!(line.center_padding(Limit::Sign), 0);
+	}
+
+	#[test]
+	fn splits_on_newline_escapes_only() {
+		assert_eq!(
+			split_lines("a\\nb\\&c\\\\"),
+			vec!["a".to_string(), "b\\&c\\\\".to_string()]
+		);
+	}
+}
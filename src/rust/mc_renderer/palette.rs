@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fs;
+
+use image::Rgb;
+use serde::Deserialize;
+
+/// Game editions, which differ in the color codes they know and how they shade text shadows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Edition {
+	#[default]
+	Java,
+	/// Adds `&g` and the material colors, which take over `&m` and `&n` since Bedrock has no
+	/// strikethrough or underline.
+	Bedrock,
+}
+
+impl Edition {
+	pub const ALL: [Edition; 2] = [Edition::Java, Edition::Bedrock];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			| Edition::Java => "Java Edition",
+			| Edition::Bedrock => "Bedrock Edition",
+		}
+	}
+
+	/// Codes that change the formatting rather than the color.
+	fn format_codes(&self) -> &'static str {
+		match self {
+			| Edition::Java => "klmnor",
+			| Edition::Bedrock => "klor",
+		}
+	}
+}
+
+/// Java's 16 colors, which every edition has.
+const JAVA_COLORS: [(char, [u8; 3]); 16] = [
+	('0', [0, 0, 0]),       // black
+	('1', [0, 0, 170]),     // dark blue
+	('2', [0, 170, 0]),     // dark green
+	('3', [0, 170, 170]),   // dark aqua
+	('4', [170, 0, 0]),     // dark red
+	('5', [170, 0, 170]),   // dark purple
+	('6', [255, 170, 0]),   // gold
+	('7', [170, 170, 170]), // gray
+	('8', [85, 85, 85]),    // dark gray
+	('9', [85, 85, 255]),   // blue
+	('a', [85, 255, 85]),   // green
+	('b', [85, 255, 255]),  // aqua
+	('c', [255, 85, 85]),   // red
+	('d', [255, 85, 255]),  // light purple
+	('e', [255, 255, 85]),  // yellow
+	('f', [255, 255, 255]), // white
+];
+
+/// Colors Bedrock adds on top of Java's 16.
+const BEDROCK_COLORS: [(char, [u8; 3]); 12] = [
+	('g', [221, 214, 5]),   // minecoin gold
+	('h', [227, 212, 209]), // material quartz
+	('i', [206, 202, 202]), // material iron
+	('j', [68, 58, 59]),    // material netherite
+	('m', [151, 22, 7]),    // material redstone
+	('n', [180, 104, 77]),  // material copper
+	('p', [222, 177, 45]),  // material gold
+	('q', [71, 160, 54]),   // material emerald
+	('s', [44, 186, 168]),  // material diamond
+	('t', [33, 73, 123]),   // material lapis
+	('u', [154, 92, 198]),  // material amethyst
+	('v', [235, 113, 20]),  // material resin
+];
+
+/// The color codes text can use and the shadows drawn under them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+	edition: Edition,
+	colors:  HashMap<char, Rgb<u8>>,
+	/// Shadows that aren't the usual quarter of their text's brightness.
+	shadows: HashMap<char, Rgb<u8>>,
+}
+
+/// A palette file, e.g. `{"colors": {"a": "#66ee66"}, "shadows": {"a": "#1a3b1a"}}`.
+#[derive(Deserialize)]
+struct PaletteFile {
+	#[serde(default)]
+	colors:  HashMap<char, String>,
+	#[serde(default)]
+	shadows: HashMap<char, String>,
+}
+
+impl Default for Palette {
+	fn default() -> Self { Palette::java() }
+}
+
+impl Palette {
+	pub fn java() -> Self {
+		Palette {
+			edition: Edition::Java,
+			colors:  HashMap::from(JAVA_COLORS.map(|(code, color)| (code, Rgb(color)))),
+			shadows: HashMap::new(),
+		}
+	}
+
+	pub fn bedrock() -> Self {
+		let mut colors = Palette::java().colors;
+		colors.extend(BEDROCK_COLORS.map(|(code, color)| (code, Rgb(color))));
+		Palette {
+			edition: Edition::Bedrock,
+			colors,
+			// Bedrock rounds gold's shadow up where Java rounds down
+			shadows: HashMap::from([('6', Rgb([64, 42, 0]))]),
+		}
+	}
+
+	pub fn for_edition(edition: Edition) -> Self {
+		match edition {
+			| Edition::Java => Palette::java(),
+			| Edition::Bedrock => Palette::bedrock(),
+		}
+	}
+
+	/// Loads a palette file over `edition`'s palette, for resource packs that recolor text. Codes
+	/// the edition doesn't have are added, and a recolored code without a shadow in the file gets
+	/// the usual one.
+	pub fn load(
+		path: &str,
+		edition: Edition,
+	) -> Result<Self, String> {
+		let json =
+			fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+		let file: PaletteFile =
+			serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", path, e))?;
+
+		let mut palette = Palette::for_edition(edition);
+		for (code, color) in file.colors {
+			let color = palette.check(code, &color)?;
+			palette.colors.insert(code, color);
+			palette.shadows.remove(&code);
+		}
+		for (code, shadow) in file.shadows {
+			let shadow = palette.check(code, &shadow)?;
+			if !palette.colors.contains_key(&code) {
+				return Err(format!("&{} has a shadow but no color", code));
+			}
+			palette.shadows.insert(code, shadow);
+		}
+		Ok(palette)
+	}
+
+	/// Parses a `#rrggbb` color for `code`, which can't be one of the formatting codes.
+	fn check(
+		&self,
+		code: char,
+		color: &str,
+	) -> Result<Rgb<u8>, String> {
+		if self.edition.format_codes().contains(code) || code == '&' || code == '§' {
+			return Err(format!(
+				"&{} is a formatting code in {}",
+				code,
+				self.edition.name()
+			));
+		}
+		hex_color(color).ok_or_else(|| format!("{} for &{} is not a #rrggbb color", color, code))
+	}
+
+	pub fn edition(&self) -> Edition { self.edition }
+
+	pub fn color(
+		&self,
+		code: char,
+	) -> Option<Rgb<u8>> {
+		self.colors.get(&code).copied()
+	}
+
+	/// Every color code with its color, in `0-9a-z` order.
+	pub fn colors(&self) -> Vec<(char, Rgb<u8>)> {
+		let mut colors: Vec<(char, Rgb<u8>)> = self
+			.colors
+			.iter()
+			.map(|(code, color)| (*code, *color))
+			.collect();
+		colors.sort_by_key(|(code, _)| *code);
+		colors
+	}
+
+	/// The shadow drawn under text of `color`. When codes that share a color have their own
+	/// shadows, the first code in `0-9a-z` order decides.
+	pub fn shadow(
+		&self,
+		color: Rgb<u8>,
+	) -> Rgb<u8> {
+		self.shadows
+			.iter()
+			.filter(|(code, _)| self.colors.get(code) == Some(&color))
+			.min_by_key(|(code, _)| **code)
+			.map(|(_, shadow)| *shadow)
+			.unwrap_or(Rgb(color.0.map(|channel| channel / 4)))
+	}
+}
+
+fn hex_color(color: &str) -> Option<Rgb<u8>> {
+	let hex = color.strip_prefix('#')?;
+	if hex.len() != 6 {
+		return None;
+	}
+	let value = u32::from_str_radix(hex, 16).ok()?;
+	Some(Rgb([(value >> 16) as u8, (value >> 8) as u8, value as u8]))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mc_renderer::parse_text_with;
+	use crate::stuff::TempPath;
+
+	#[test]
+	fn bedrock_codes_replace_java_formats() {
+		let java = parse_text_with("&g&mA", &Palette::java());
+		assert_eq!(java[0][0].1.color, Rgb([255, 255, 255]));
+		assert!(java[0][0].1.strikethrough);
+
+		let bedrock = parse_text_with("&g&mA", &Palette::bedrock());
+		assert_eq!(bedrock[0][0].1.color, Rgb([151, 22, 7]));
+		assert!(!bedrock[0][0].1.strikethrough);
+	}
+
+	#[test]
+	fn shadows_follow_the_edition() {
+		let gold = Rgb([255, 170, 0]);
+		assert_eq!(Palette::java().shadow(gold), Rgb([63, 42, 0]));
+		assert_eq!(Palette::bedrock().shadow(gold), Rgb([64, 42, 0]));
+		assert_eq!(
+			Palette::bedrock().shadow(Rgb([221, 214, 5])),
+			Rgb([55, 53, 1])
+		);
+	}
+
+	#[test]
+	fn loads_palette_files() {
+		let path = TempPath::new("loads_palette_files.json");
+		let write = |json: &str| fs::write(&path.0, json).unwrap();
+		let path = path.0.to_str().unwrap();
+
+		write(r##"{"colors": {"a": "#66ee66", "6": "#eeaa11"}, "shadows": {"a": "#112211"}}"##);
+		let palette = Palette::load(path, Edition::Bedrock).unwrap();
+		assert_eq!(palette.color('a'), Some(Rgb([102, 238, 102])));
+		assert_eq!(palette.shadow(Rgb([102, 238, 102])), Rgb([17, 34, 17]));
+		// recoloring gold drops Bedrock's own shadow for it
+		assert_eq!(palette.shadow(Rgb([238, 170, 17])), Rgb([59, 42, 4]));
+		assert_eq!(palette.color('g'), Some(Rgb([221, 214, 5])));
+
+		// codes sharing a color always get the same shadow, whatever order they're stored in
+		write(
+			r##"{"colors": {"x": "#808080", "w": "#808080"}, "shadows": {"x": "#010101", "w": "#020202"}}"##,
+		);
+		let palette = Palette::load(path, Edition::Java).unwrap();
+		assert_eq!(palette.shadow(Rgb([128, 128, 128])), Rgb([2, 2, 2]));
+
+		write(r##"{"colors": {"m": "#ffffff"}}"##);
+		assert!(Palette::load(path, Edition::Java).is_err());
+		assert!(Palette::load(path, Edition::Bedrock).is_ok());
+		write(r##"{"colors": {"a": "green"}}"##);
+		assert!(Palette::load(path, Edition::Java).is_err());
+	}
+}
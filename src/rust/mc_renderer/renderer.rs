@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use image::{Rgb, RgbImage, Rgba, RgbaImage};
+use rusttype::{Font, Scale};
+
+use super::align::{Align, split_alignments};
+use super::head::{draw_inline_heads, inline_heads, load_head};
+use super::palette::Palette;
+use super::{
+	LINE_HEIGHT,
+	Layout,
+	Style,
+	TextOptions,
+	draw_line,
+	line_width,
+	load_background,
+	load_fonts,
+	output_size,
+	parse_text_with,
+	render,
+	shadow_line_with,
+};
+
+const DEFAULT_MARGINS: (f32, f32) = (10.0, 10.0);
+
+/// Renders formatted text with settings chosen once, for tools that use the crate as a library.
+///
+/// ```
+/// use random_things::Renderer;
+///
+/// let renderer = Renderer::new().with_scale(32.0).with_shadow(true);
+/// let image = renderer.render("&6Hello &lworld");
+/// assert_eq!(image.dimensions(), renderer.measure("&6Hello &lworld"));
+/// ```
+///
+/// The chat and tooltip layouts place their text like vanilla does, so they round the scale to a
+/// gui scale and ignore the margins and skins. Their background defaults to the bundled
+/// screenshot.
+#[derive(Clone)]
+pub struct Renderer {
+	fonts:       HashMap<&'static str, Font<'static>>,
+	scale:       f32,
+	margins:     (f32, f32),
+	line_height: f32,
+	layout:      Layout,
+	background:  Option<RgbImage>,
+	shadow:      bool,
+	skins:       Option<PathBuf>,
+	palette:     Palette,
+	align:       Align,
+}
+
+impl Default for Renderer {
+	fn default() -> Self { Renderer::new() }
+}
+
+impl Renderer {
+	/// A renderer using the bundled fonts at 16 pixels per line, without a background.
+	pub fn new() -> Self {
+		Renderer {
+			fonts:       load_fonts(),
+			scale:       16.0,
+			margins:     DEFAULT_MARGINS,
+			line_height: LINE_HEIGHT,
+			layout:      Layout::default(),
+			background:  None,
+			shadow:      false,
+			skins:       None,
+			palette:     Palette::java(),
+			align:       Align::Left,
+		}
+	}
+
+	/// Fonts keyed by `regular`, `bold`, `italic` and `bold_italic`.
+	pub fn with_fonts(
+		mut self,
+		fonts: HashMap<&'static str, Font<'static>>,
+	) -> Self {
+		self.fonts = fonts;
+		self
+	}
+
+	/// Height of a line of text in pixels, 8 font pixels tall.
+	pub fn with_scale(
+		mut self,
+		scale: f32,
+	) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	/// Space left and right, and above and below the text.
+	pub fn with_margins(
+		mut self,
+		horizontal: f32,
+		vertical: f32,
+	) -> Self {
+		self.margins = (horizontal, vertical);
+		self
+	}
+
+	/// Distance between lines as a multiple of the scale.
+	pub fn with_line_height(
+		mut self,
+		line_height: f32,
+	) -> Self {
+		self.line_height = line_height;
+		self
+	}
+
+	pub fn with_layout(
+		mut self,
+		layout: Layout,
+	) -> Self {
+		self.layout = layout;
+		self
+	}
+
+	/// Image to draw the text over. Without one the text is drawn on a transparent image that
+	/// fits it.
+	pub fn with_background(
+		mut self,
+		background: Option<RgbImage>,
+	) -> Self {
+		self.background = background;
+		self
+	}
+
+	pub fn with_shadow(
+		mut self,
+		shadow: bool,
+	) -> Self {
+		self.shadow = shadow;
+		self
+	}
+
+	/// Folder of `<name>.png` skins that `{head:Name}` placeholders are drawn from.
+	pub fn with_skins(
+		mut self,
+		skins: Option<PathBuf>,
+	) -> Self {
+		self.skins = skins;
+		self
+	}
+
+	/// Color codes and shadows, Java Edition's by default.
+	pub fn with_palette(
+		mut self,
+		palette: Palette,
+	) -> Self {
+		self.palette = palette;
+		self
+	}
+
+	/// Alignment of lines before the first `{align:...}` directive. Lines are aligned in the
+	/// background between the margins, or to the widest line without one.
+	pub fn with_align(
+		mut self,
+		align: Align,
+	) -> Self {
+		self.align = align;
+		self
+	}
+
+	pub fn scale(&self) -> Scale { Scale::uniform(self.scale) }
+
+	pub fn fonts(&self) -> &HashMap<&'static str, Font<'static>> { &self.fonts }
+
+	pub fn margins(&self) -> (f32, f32) { self.margins }
+
+	pub fn line_height(&self) -> f32 { self.line_height }
+
+	pub fn shadow(&self) -> bool { self.shadow }
+
+	pub fn palette(&self) -> &Palette { &self.palette }
+
+	pub fn align(&self) -> Align { self.align }
+
+	fn gui_scale(&self) -> u32 { ((self.scale / 8.0).round() as u32).max(1) }
+
+	/// Baseline of the first line, glyphs reaching 7 of their 8 font pixels above it.
+	pub fn origin(&self) -> (f32, f32) { (self.margins.0, self.margins.1 + self.scale * 7.0 / 8.0) }
+
+	fn text_options(&self) -> TextOptions<'_> {
+		TextOptions {
+			palette:     &self.palette,
+			align:       self.align,
+			shadow:      self.shadow,
+			line_height: self.line_height,
+		}
+	}
+
+	/// Size of the image `render` returns for `text`.
+	pub fn measure(
+		&self,
+		text: &str,
+	) -> (u32, u32) {
+		let (text, _) = self.inline_heads(text);
+		let (text, _) = split_alignments(&text, self.align);
+		let lines = parse_text_with(&text, &self.palette);
+
+		match (self.layout, &self.background) {
+			| (Layout::Screenshot, Some(background)) => background.dimensions(),
+			| (Layout::Screenshot, None) => {
+				let scale = self.scale();
+				let text_width = lines
+					.iter()
+					.map(|line| line_width(line, &self.fonts, scale))
+					.fold(0.0, f32::max);
+				// glyphs reach 7 font pixels above the baseline, and underlines 2 below it
+				let text_height = (lines.len() as f32 - 1.0) * self.scale * self.line_height
+					+ self.scale * 9.0 / 8.0;
+				// the shadow reaches one font pixel past the text
+				let shadow = if self.shadow { self.scale / 8.0 } else { 0.0 };
+				(
+					(text_width + shadow + self.margins.0 * 2.0).ceil() as u32,
+					(text_height + shadow + self.margins.1 * 2.0).ceil() as u32,
+				)
+			},
+			| (layout, background) => {
+				let background = background.clone().unwrap_or_else(load_background);
+				output_size(
+					&text,
+					&self.fonts,
+					&background,
+					layout,
+					self.gui_scale(),
+					&self.text_options(),
+				)
+			},
+		}
+	}
+
+	pub fn render(
+		&self,
+		text: &str,
+	) -> RgbaImage {
+		if self.layout != Layout::Screenshot {
+			let background = self.background.clone().unwrap_or_else(load_background);
+			let image = render(
+				text,
+				&self.fonts,
+				&background,
+				self.layout,
+				self.gui_scale(),
+				&self.text_options(),
+			);
+			return opaque(&image);
+		}
+
+		match &self.background {
+			| Some(background) => {
+				let mut image = background.clone();
+				self.draw(text, &mut image);
+				opaque(&image)
+			},
+			| None => {
+				let (width, height) = self.measure(text);
+				let mut black = RgbImage::new(width, height);
+				let mut white = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+				self.draw(text, &mut black);
+				self.draw(text, &mut white);
+				transparent(&black, &white)
+			},
+		}
+	}
+
+	/// Swaps `{head:Name}` placeholders for the heads found in the skins folder.
+	pub(super) fn inline_heads(
+		&self,
+		text: &str,
+	) -> (String, Vec<RgbaImage>) {
+		match (&self.skins, self.layout) {
+			| (Some(skins), Layout::Screenshot) => inline_heads(text, |name| {
+				load_head(&skins.join(format!("{}.png", name))).ok()
+			}),
+			| _ => (text.to_string(), Vec::new()),
+		}
+	}
+
+	/// Lays `text` out like the screenshot layout draws it.
+	fn place_lines(
+		&self,
+		text: &str,
+	) -> PlacedText {
+		let scale = self.scale();
+		let unit = self.scale / 8.0;
+		let origin = self.origin();
+		let (text, heads) = self.inline_heads(text);
+		let (text, alignments) = split_alignments(&text, self.align);
+		let lines = parse_text_with(&text, &self.palette);
+
+		let widths: Vec<f32> = lines
+			.iter()
+			.map(|line| line_width(line, &self.fonts, scale))
+			.collect();
+		let width = match &self.background {
+			| Some(background) => background.width() as f32 - self.margins.0 * 2.0,
+			| None => widths.iter().copied().fold(0.0, f32::max),
+		};
+
+		let origins = (0..lines.len())
+			.map(|i| {
+				(
+					origin.0 + alignments[i].offset(widths[i], width, unit),
+					origin.1 + i as f32 * self.scale * self.line_height,
+				)
+			})
+			.collect();
+		PlacedText {
+			lines,
+			origins,
+			widths,
+			heads,
+		}
+	}
+
+	/// Where the screenshot layout draws `text` in its image, as the left, top, right and bottom
+	/// edges of the text from the top of its first line to the baseline of its last.
+	pub fn text_bounds(
+		&self,
+		text: &str,
+	) -> (f32, f32, f32, f32) {
+		let placed = self.place_lines(text);
+		let (mut left, mut right) = (f32::MAX, f32::MIN);
+		for ((x, _), width) in placed.origins.iter().zip(&placed.widths) {
+			left = left.min(*x);
+			right = right.max(x + width);
+		}
+		let top = self.origin().1 - self.scale;
+		let bottom = placed.origins.last().map_or(top, |(_, y)| *y);
+		(left, top, right, bottom)
+	}
+
+	fn draw(
+		&self,
+		text: &str,
+		image: &mut RgbImage,
+	) {
+		let scale = self.scale();
+		let unit = self.scale / 8.0;
+		let PlacedText {
+			lines,
+			origins,
+			heads,
+			..
+		} = self.place_lines(text);
+
+		for (line, &(x, y)) in lines.iter().zip(&origins) {
+			if self.shadow {
+				draw_line(
+					&shadow_line_with(line, &self.palette),
+					&self.fonts,
+					image,
+					(x + unit, y + unit),
+					scale,
+				);
+			}
+			draw_line(line, &self.fonts, image, (x, y), scale);
+			draw_inline_heads(image, line, &heads, &self.fonts, scale, (x, y));
+		}
+	}
+}
+
+/// Lines of text with the baseline each one starts at, its width, and the heads drawn in them.
+struct PlacedText {
+	lines:   Vec<Vec<(char, Style)>>,
+	origins: Vec<(f32, f32)>,
+	widths:  Vec<f32>,
+	heads:   Vec<RgbaImage>,
+}
+
+fn opaque(image: &RgbImage) -> RgbaImage {
+	RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+		let Rgb([r, g, b]) = *image.get_pixel(x, y);
+		Rgba([r, g, b, 255])
+	})
+}
+
+/// Recovers color and coverage from the same text drawn over black and over white. Drawing only
+/// blends towards the text colors, so the difference between the two is what the background
+/// still shows through.
+fn transparent(
+	black: &RgbImage,
+	white: &RgbImage,
+) -> RgbaImage {
+	RgbaImage::from_fn(black.width(), black.height(), |x, y| {
+		let (over_black, over_white) = (black.get_pixel(x, y).0, white.get_pixel(x, y).0);
+		let shown: u32 = (0..3)
+			.map(|i| over_white[i].saturating_sub(over_black[i]) as u32)
+			.sum();
+		let alpha = 255 - (shown / 3) as u8;
+		if alpha == 0 {
+			return Rgba([0, 0, 0, 0]);
+		}
+
+		let color = over_black.map(|channel| (channel as u32 * 255 / alpha as u32).min(255) as u8);
+		Rgba([color[0], color[1], color[2], alpha])
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mc_renderer::render_text;
+	use crate::stuff::TempPath;
+
+	#[test]
+	fn measures_text_with_margins() {
+		let renderer = Renderer::new().with_margins(0.0, 0.0);
+		let (width, height) = renderer.measure("Hi");
+		assert_eq!(height, 18);
+		assert!(width > 0 && width < 32);
+
+		let (_, two_lines) = renderer.with_line_height(2.0).measure("a\\nb");
+		assert_eq!(two_lines, 16 * 2 + 18);
+	}
+
+	#[test]
+	fn keeps_underlines_of_the_last_line() {
+		let renderer = Renderer::new().with_margins(0.0, 0.0);
+		let bottom_row_lit = |text: &str| {
+			let image = renderer.render(text);
+			let bottom = image.height() - 1;
+			(0..image.width()).any(|x| image.get_pixel(x, bottom)[3] > 0)
+		};
+		assert!(bottom_row_lit("&nHi"));
+		assert!(!bottom_row_lit("Hi"));
+	}
+
+	#[test]
+	fn renders_transparent_text() {
+		let image = Renderer::new().render("&c#");
+		assert_eq!(image.dimensions(), Renderer::new().measure("&c#"));
+		assert_eq!(image.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+		assert!(
+			image
+				.pixels()
+				.any(|pixel| *pixel == Rgba([255, 85, 85, 255]))
+		);
+	}
+
+	#[test]
+	fn matches_render_text_over_background() {
+		let background = RgbImage::from_pixel(200, 60, Rgb([48, 48, 48]));
+		let renderer = Renderer::new()
+			.with_background(Some(background.clone()))
+			.with_shadow(true);
+
+		let mut expected = background;
+		render_text(
+			"&eHello",
+			&load_fonts(),
+			&mut expected,
+			Scale::uniform(16.0),
+			renderer.origin(),
+			&renderer.text_options(),
+		);
+		assert_eq!(renderer.render("&eHello"), opaque(&expected));
+	}
+
+	#[test]
+	fn draws_inline_heads_from_skins() {
+		let skins = TempPath::new("draws_inline_heads_from_skins");
+		std::fs::create_dir_all(&skins.0).unwrap();
+		let skin = RgbaImage::from_pixel(64, 64, Rgba([0, 200, 0, 255]));
+		skin.save(skins.0.join("Steve.png")).unwrap();
+
+		let renderer = Renderer::new()
+			.with_margins(0.0, 0.0)
+			.with_skins(Some(skins.0.clone()));
+		let (width, _) = renderer.measure("{head:Steve}");
+		assert_eq!(width, 18);
+
+		let image = renderer.render("{head:Steve}{head:Nobody}");
+		assert_eq!(image.get_pixel(0, 0), &Rgba([0, 200, 0, 255]));
+		assert_eq!(image.get_pixel(15, 15), &Rgba([0, 200, 0, 255]));
+		assert_eq!(image.get_pixel(16, 0)[3], 0);
+	}
+
+	#[test]
+	fn aligns_lines_between_margins() {
+		let renderer = Renderer::new()
+			.with_margins(10.0, 0.0)
+			.with_background(Some(RgbImage::new(100, 40)));
+		let first_lit_column = |image: RgbaImage| {
+			(0..image.width()).find(|&x| (0..image.height()).any(|y| image.get_pixel(x, y)[0] > 0))
+		};
+
+		assert_eq!(first_lit_column(renderer.render("i")), Some(10));
+		// the 4 pixel wide line moves over the 76 pixels left between the margins
+		assert_eq!(
+			first_lit_column(renderer.clone().with_align(Align::Right).render("i")),
+			Some(86)
+		);
+		assert_eq!(
+			first_lit_column(renderer.render("{align:center}i")),
+			Some(48)
+		);
+	}
+
+	#[test]
+	fn layouts_match_their_measured_size() {
+		for layout in [Layout::Chat, Layout::Tooltip] {
+			let renderer = Renderer::new().with_layout(layout);
+			let text = "Name\\nLore";
+			assert_eq!(renderer.render(text).dimensions(), renderer.measure(text));
+		}
+	}
+
+	#[test]
+	fn vanilla_layouts_use_the_text_options() {
+		let tooltip = Renderer::new()
+			.with_layout(Layout::Tooltip)
+			.with_margins(0.0, 0.0)
+			.with_skins(Some(PathBuf::from("skins")));
+		let text = "A long name\\n{align:right}i";
+		assert_eq!(tooltip.measure(text), tooltip.render(text).dimensions());
+
+		// the short line moves to the right edge of the long one
+		let lit_columns = |renderer: &Renderer| {
+			let image = renderer.render("A long name\\ni");
+			(0..image.width())
+				.filter(|&x| (32..46).any(|y| image.get_pixel(x, y)[0] > 200))
+				.collect::<Vec<_>>()
+		};
+		let left = lit_columns(&tooltip);
+		let right = lit_columns(&tooltip.clone().with_align(Align::Right));
+		assert!(right[0] > left[0] + 50, "{:?} {:?}", left, right);
+
+		let bedrock = Renderer::new()
+			.with_layout(Layout::Chat)
+			.with_palette(Palette::bedrock());
+		let red = |renderer: &Renderer| {
+			renderer
+				.render("&m####")
+				.pixels()
+				.any(|pixel| pixel.0 == [151, 22, 7, 255])
+		};
+		assert!(red(&bedrock));
+		assert!(!red(&Renderer::new().with_layout(Layout::Chat)));
+
+		let spaced = Renderer::new()
+			.with_layout(Layout::Chat)
+			.with_line_height(2.0);
+		assert!(
+			spaced.measure("a\\nb").1
+				> Renderer::new().with_layout(Layout::Chat).measure("a\\nb").1
+		);
+	}
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use image::{RgbImage, RgbaImage};
+use rusttype::{Font, Scale};
+
+use super::head::{draw_head, draw_inline_heads, head_names, inline_heads, load_head};
+use super::palette::Palette;
+use super::{
+	LINE_HEIGHT,
+	chat_backdrop,
+	draw_line,
+	load_background,
+	load_fonts,
+	parse_text_with,
+	prompt_palette,
+	prompt_save,
+	shadow_line_with,
+};
+use crate::main;
+use crate::stuff::input;
+
+/// One message of a chat scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+	pub timestamp: Option<String>,
+	/// Name of the player who sent the message, `None` for system messages.
+	pub player:    Option<String>,
+	pub text:      String,
+}
+
+impl Message {
+	/// The message as it appears in chat, e.g. `[12:01] <Steve> hello`.
+	pub fn line(&self) -> String {
+		let mut line = String::new();
+		if let Some(timestamp) = &self.timestamp {
+			line.push_str(&format!("&7[{}]&r ", timestamp));
+		}
+		if let Some(player) = &self.player {
+			line.push_str(&format!("<{}&r> ", player));
+		}
+		line.push_str(&self.text);
+		line
+	}
+}
+
+/// Parses a scene description, one message per line in the order they appear in chat:
+///
+/// ```text
+/// # lines starting with # are comments
+/// [12:01] <Steve> hello
+/// <Alex> &ahi Steve!
+/// &eSteve joined the game
+/// ```
+///
+/// A message can start with a `[hh:mm]` timestamp, then `<Name>` makes it a player's message and
+/// anything else is a system message. Messages use `&` codes like the renderer, and
+/// `{head:Name}` for a player's head.
+pub fn parse_scene(source: &str) -> Vec<Message> {
+	source
+		.lines()
+		.filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+		.map(|line| {
+			let mut rest = line.trim_end();
+
+			let timestamp = rest
+				.strip_prefix('[')
+				.and_then(|after| after.split_once("] "))
+				.filter(|(time, _)| {
+					!time.is_empty() && time.chars().all(|c| c.is_ascii_digit() || c == ':')
+				})
+				.map(|(time, after)| {
+					rest = after;
+					time.to_string()
+				});
+
+			let player = rest
+				.strip_prefix('<')
+				.and_then(|after| after.split_once('>'))
+				.filter(|(name, _)| !name.is_empty())
+				.map(|(name, after)| {
+					rest = after.strip_prefix(' ').unwrap_or(after);
+					name.to_string()
+				});
+
+			Message {
+				timestamp,
+				player,
+				text: rest.to_string(),
+			}
+		})
+		.collect()
+}
+
+/// A player's name without formatting codes, which is also the file name of their skin.
+fn plain_name(
+	name: &str,
+	palette: &Palette,
+) -> String {
+	parse_text_with(name, palette)
+		.concat()
+		.into_iter()
+		.map(|(c, _)| c)
+		.collect()
+}
+
+/// Loads the heads of every player in `messages` and their `{head:Name}` placeholders that has a
+/// `<name>.png` skin in `skins`.
+pub fn load_heads(
+	messages: &[Message],
+	skins: &Path,
+	palette: &Palette,
+) -> HashMap<String, RgbaImage> {
+	let names = messages.iter().flat_map(|message| {
+		let sender = message
+			.player
+			.as_deref()
+			.map(|name| plain_name(name, palette));
+		sender.into_iter().chain(head_names(&message.text))
+	});
+
+	let mut heads = HashMap::new();
+	for name in names {
+		if heads.contains_key(&name) {
+			continue;
+		}
+		match load_head(&skins.join(format!("{}.png", name))) {
+			| Ok(head) => {
+				heads.insert(name, head);
+			},
+			| Err(e) => println!("\x1b[33mNo head for {}:\x1b[0m {}", name, e),
+		}
+	}
+	heads
+}
+
+/// Renders `messages` in the chat layout, with a head before the messages of players in `heads`.
+pub fn render_scene(
+	messages: &[Message],
+	heads: &HashMap<String, RgbaImage>,
+	fonts: &HashMap<&str, Font>,
+	background: &RgbImage,
+	gui_scale: u32,
+	palette: &Palette,
+) -> RgbImage {
+	let unit = gui_scale as f32;
+	let scale = Scale::uniform(8.0 * unit);
+	let line_height = scale.y * LINE_HEIGHT;
+
+	let rows: Vec<_> = messages
+		.iter()
+		.map(|message| {
+			let head = message
+				.player
+				.as_deref()
+				.and_then(|name| heads.get(&plain_name(name, palette)));
+			let (line, inline) = inline_heads(&message.line(), |name| heads.get(name).cloned());
+			(head, parse_text_with(&line, palette), inline)
+		})
+		.collect();
+	let line_count = rows.iter().map(|(_, lines, _)| lines.len()).sum();
+
+	let mut image = chat_backdrop(background, gui_scale, line_count, LINE_HEIGHT);
+	let mut row = 0;
+	for (head, lines, inline) in rows {
+		// a head takes 8 pixels and a pixel of space, its message lines up after it
+		let x = if head.is_some() { 10.0 * unit } else { unit };
+		if let Some(head) = head {
+			draw_head(
+				&mut image,
+				head,
+				(unit as i64, (row as f32 * line_height) as i64),
+				8 * gui_scale,
+			);
+		}
+
+		for line in lines {
+			let y = 7.0 * unit + row as f32 * line_height;
+			draw_line(
+				&shadow_line_with(&line, palette),
+				fonts,
+				&mut image,
+				(x + unit, y + unit),
+				scale,
+			);
+			draw_line(&line, fonts, &mut image, (x, y), scale);
+			draw_inline_heads(&mut image, &line, &inline, fonts, scale, (x, y));
+			row += 1;
+		}
+	}
+
+	image
+}
+
+pub fn start() {
+	let path = input("\nPath to the scene file:", true);
+	let source = match fs::read_to_string(&path) {
+		| Ok(source) => source,
+		| Err(e) => {
+			println!("\x1b[31mFailed to read {}:\x1b[0m {}", path, e);
+			return main();
+		},
+	};
+	let messages = parse_scene(&source);
+	if messages.is_empty() {
+		println!("\nThe scene has no messages");
+		return main();
+	}
+	let palette = prompt_palette();
+
+	let skins = input(
+		"\nFolder with <name>.png skins for player heads (leave empty for no heads):",
+		true,
+	);
+	let heads = if skins.is_empty() {
+		HashMap::new()
+	} else {
+		load_heads(&messages, Path::new(&skins), &palette)
+	};
+
+	let image = render_scene(
+		&messages,
+		&heads,
+		&load_fonts(),
+		&load_background(),
+		2,
+		&palette,
+	);
+
+	prompt_save(&image);
+
+	main()
+}
+
+#[cfg(test)]
+mod tests {
+	use image::Rgba;
+
+	use super::*;
+	use crate::mc_renderer::{Layout, TextOptions, render};
+
+	const SCENE: &str =
+		"# a conversation\n[12:01] <Steve> hello\n\n<&cAlex> hi\n[Server] restarting";
+
+	#[test]
+	fn parses_messages() {
+		let messages = parse_scene(SCENE);
+		assert_eq!(
+			messages,
+			vec![
+				Message {
+					timestamp: Some("12:01".to_string()),
+					player:    Some("Steve".to_string()),
+					text:      "hello".to_string(),
+				},
+				Message {
+					timestamp: None,
+					player:    Some("&cAlex".to_string()),
+					text:      "hi".to_string(),
+				},
+				Message {
+					timestamp: None,
+					player:    None,
+					text:      "[Server] restarting".to_string(),
+				},
+			]
+		);
+		assert_eq!(messages[0].line(), "&7[12:01]&r <Steve&r> hello");
+		assert_eq!(plain_name("&cAlex", &Palette::java()), "Alex");
+	}
+
+	#[test]
+	fn matches_chat_layout_without_heads() {
+		let messages = parse_scene(SCENE);
+		let lines: Vec<String> = messages.iter().map(Message::line).collect();
+		let (fonts, background) = (load_fonts(), load_background());
+
+		let palette = Palette::bedrock();
+		let scene = render_scene(&messages, &HashMap::new(), &fonts, &background, 2, &palette);
+		let chat = render(
+			&lines.join("\\n"),
+			&fonts,
+			&background,
+			Layout::Chat,
+			2,
+			&TextOptions::vanilla(&palette, Layout::Chat),
+		);
+		assert_eq!(scene, chat);
+	}
+
+	#[test]
+	fn draws_heads_before_messages() {
+		let messages = parse_scene("<Steve> hello");
+		let head = RgbaImage::from_pixel(8, 8, Rgba([0, 255, 0, 255]));
+		let heads = HashMap::from([("Steve".to_string(), head)]);
+
+		let image = render_scene(
+			&messages,
+			&heads,
+			&load_fonts(),
+			&load_background(),
+			1,
+			&Palette::java(),
+		);
+		assert_eq!(image.get_pixel(1, 0).0, [0, 255, 0]);
+		assert_eq!(image.get_pixel(8, 7).0, [0, 255, 0]);
+		assert_ne!(image.get_pixel(9, 0).0, [0, 255, 0]);
+	}
+}
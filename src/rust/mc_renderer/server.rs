@@ -0,0 +1,568 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{Receiver, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use image::{ImageFormat, RgbImage};
+use rusttype::Font;
+use serde::Deserialize;
+
+use super::palette::Palette;
+use super::{Layout, TextOptions, load_background, load_fonts, output_size, render};
+use crate::main;
+use crate::stuff::input;
+
+const DEFAULT_PORT: u16 = 8025;
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 16 * 1024;
+const MAX_TEXT_CHARS: usize = 2048;
+const MAX_GUI_SCALE: u32 = 8;
+/// How long a client gets to send its whole request, so a slow one can't hold a worker.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Largest image rendered, which keeps a long tooltip line at a high scale from taking gigabytes.
+const MAX_PIXELS: u64 = 8_000_000;
+const CACHE_CAPACITY: usize = 64;
+/// Connections handled at once, which bounds how much memory renders can take together.
+const WORKERS: usize = 4;
+/// Accepted connections that wait for a worker before new ones are left to the OS backlog.
+const QUEUE_LENGTH: usize = 16;
+
+type CacheKey = (String, Layout, u32);
+
+/// Keeps the most recently requested renders as encoded PNG bytes.
+struct RenderCache {
+	capacity: usize,
+	order:    VecDeque<CacheKey>,
+	entries:  HashMap<CacheKey, Arc<Vec<u8>>>,
+}
+
+impl RenderCache {
+	fn new(capacity: usize) -> Self {
+		RenderCache {
+			capacity,
+			order: VecDeque::new(),
+			entries: HashMap::new(),
+		}
+	}
+
+	fn get(
+		&mut self,
+		key: &CacheKey,
+	) -> Option<Arc<Vec<u8>>> {
+		let png = self.entries.get(key)?.clone();
+		self.order.retain(|k| k != key);
+		self.order.push_back(key.clone());
+		Some(png)
+	}
+
+	fn insert(
+		&mut self,
+		key: CacheKey,
+		png: Arc<Vec<u8>>,
+	) {
+		if self.entries.insert(key.clone(), png).is_some() {
+			self.order.retain(|k| k != &key);
+		}
+		self.order.push_back(key);
+
+		while self.order.len() > self.capacity {
+			if let Some(oldest) = self.order.pop_front() {
+				self.entries.remove(&oldest);
+			}
+		}
+	}
+}
+
+struct State {
+	fonts:      HashMap<&'static str, Font<'static>>,
+	background: RgbImage,
+	cache:      Mutex<RenderCache>,
+}
+
+#[derive(Deserialize)]
+struct RenderRequest {
+	text:   String,
+	#[serde(default)]
+	layout: Layout,
+	scale:  Option<u32>,
+}
+
+struct Response {
+	status:       u16,
+	content_type: &'static str,
+	body:         Arc<Vec<u8>>,
+}
+
+impl Response {
+	fn png(body: Arc<Vec<u8>>) -> Self {
+		Response {
+			status: 200,
+			content_type: "image/png",
+			body,
+		}
+	}
+
+	fn error(
+		status: u16,
+		message: &str,
+	) -> Self {
+		Response {
+			status,
+			content_type: "text/plain; charset=utf-8",
+			body: Arc::new(message.as_bytes().to_vec()),
+		}
+	}
+
+	fn write_to(
+		&self,
+		stream: &mut impl Write,
+	) -> std::io::Result<()> {
+		let reason = match self.status {
+			| 200 => "OK",
+			| 400 => "Bad Request",
+			| 404 => "Not Found",
+			| 405 => "Method Not Allowed",
+			| 413 => "Payload Too Large",
+			| 431 => "Request Header Fields Too Large",
+			| _ => "Internal Server Error",
+		};
+		write!(
+			stream,
+			"HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+			self.status,
+			reason,
+			self.content_type,
+			self.body.len()
+		)?;
+		stream.write_all(&self.body)?;
+		stream.flush()
+	}
+}
+
+fn hex_value(byte: u8) -> Option<u8> { (byte as char).to_digit(16).map(|d| d as u8) }
+
+/// Decodes `application/x-www-form-urlencoded` text, where `+` is a space.
+fn url_decode(value: &str) -> Result<String, String> {
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			| b'+' => decoded.push(b' '),
+			| b'%' => {
+				let high = bytes.get(i + 1).copied().and_then(hex_value);
+				let low = bytes.get(i + 2).copied().and_then(hex_value);
+				match (high, low) {
+					| (Some(high), Some(low)) => decoded.push(high << 4 | low),
+					| _ => return Err("Invalid percent encoding".to_string()),
+				}
+				i += 2;
+			},
+			| byte => decoded.push(byte),
+		}
+		i += 1;
+	}
+
+	String::from_utf8(decoded).map_err(|_| "Query is not valid UTF-8".to_string())
+}
+
+fn parse_query(query: &str) -> Result<RenderRequest, String> {
+	let mut text = None;
+	let mut layout = Layout::default();
+	let mut scale = None;
+
+	for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+		let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+		let value = url_decode(value)?;
+		match key {
+			| "text" => text = Some(value),
+			| "layout" => layout = value.parse()?,
+			| "scale" => {
+				scale = Some(
+					value
+						.parse()
+						.map_err(|_| format!("Invalid scale '{}'", value))?,
+				)
+			},
+			| _ => {},
+		}
+	}
+
+	Ok(RenderRequest {
+		text: text.ok_or("Missing 'text' parameter")?,
+		layout,
+		scale,
+	})
+}
+
+fn render_png(
+	request: RenderRequest,
+	state: &State,
+) -> Response {
+	if request.text.chars().count() > MAX_TEXT_CHARS {
+		return Response::error(
+			413,
+			&format!("Text is limited to {} characters", MAX_TEXT_CHARS),
+		);
+	}
+
+	let gui_scale = request.scale.unwrap_or(2);
+	if !(1..=MAX_GUI_SCALE).contains(&gui_scale) {
+		return Response::error(
+			400,
+			&format!("Scale must be between 1 and {}", MAX_GUI_SCALE),
+		);
+	}
+
+	let key = (request.text, request.layout, gui_scale);
+	if let Some(png) = state.cache.lock().unwrap().get(&key) {
+		return Response::png(png);
+	}
+
+	let palette = Palette::java();
+	let options = TextOptions::vanilla(&palette, key.1);
+	let (width, height) = output_size(
+		&key.0,
+		&state.fonts,
+		&state.background,
+		key.1,
+		key.2,
+		&options,
+	);
+	if width as u64 * height as u64 > MAX_PIXELS {
+		return Response::error(
+			413,
+			&format!(
+				"The image would be {}x{} pixels, which is more than {} pixels",
+				width, height, MAX_PIXELS
+			),
+		);
+	}
+
+	let image = render(
+		&key.0,
+		&state.fonts,
+		&state.background,
+		key.1,
+		key.2,
+		&options,
+	);
+	let mut png = Cursor::new(Vec::new());
+	if image.write_to(&mut png, ImageFormat::Png).is_err() {
+		return Response::error(500, "Failed to encode image");
+	}
+
+	let png = Arc::new(png.into_inner());
+	state.cache.lock().unwrap().insert(key, png.clone());
+	Response::png(png)
+}
+
+fn respond(
+	method: &str,
+	target: &str,
+	body: &[u8],
+	state: &State,
+) -> Response {
+	let (path, query) = target.split_once('?').unwrap_or((target, ""));
+	if path != "/render" {
+		return Response::error(404, "Not found, try /render");
+	}
+
+	let request = match method {
+		| "GET" => parse_query(query),
+		| "POST" => serde_json::from_slice(body).map_err(|e| format!("Invalid JSON body: {}", e)),
+		| _ => return Response::error(405, "Only GET and POST are supported"),
+	};
+
+	match request {
+		| Ok(request) => render_png(request, state),
+		| Err(message) => Response::error(400, &message),
+	}
+}
+
+/// Reads from a stream until `deadline`, however the client spreads out what it sends.
+struct DeadlineReader {
+	stream:   TcpStream,
+	deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+	fn read(
+		&mut self,
+		buf: &mut [u8],
+	) -> std::io::Result<usize> {
+		let remaining = self.deadline.saturating_duration_since(Instant::now());
+		if remaining.is_zero() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::TimedOut,
+				"The request took too long to arrive",
+			));
+		}
+		self.stream.set_read_timeout(Some(remaining))?;
+		self.stream.read(buf)
+	}
+}
+
+fn handle_connection(
+	stream: TcpStream,
+	state: &State,
+) -> std::io::Result<()> {
+	let deadline_reader = DeadlineReader {
+		stream:   stream.try_clone()?,
+		deadline: Instant::now() + REQUEST_TIMEOUT,
+	};
+	// bounds how much a single client can make us read
+	let limit = (MAX_HEADER_BYTES + MAX_BODY_BYTES) as u64;
+	let mut reader = BufReader::new(deadline_reader.take(limit));
+	let mut stream = stream;
+
+	let mut request_line = String::new();
+	reader.read_line(&mut request_line)?;
+	let mut parts = request_line.split_whitespace();
+	let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+		return Response::error(400, "Malformed request line").write_to(&mut stream);
+	};
+
+	let mut header_bytes = request_line.len();
+	let mut content_length = 0;
+	loop {
+		let mut line = String::new();
+		if reader.read_line(&mut line)? == 0 || line == "\r\n" || line == "\n" {
+			break;
+		}
+
+		header_bytes += line.len();
+		if header_bytes > MAX_HEADER_BYTES {
+			return Response::error(431, "Request headers are too large").write_to(&mut stream);
+		}
+
+		if let Some((name, value)) = line.split_once(':') {
+			if name.trim().eq_ignore_ascii_case("content-length") {
+				content_length = match value.trim().parse() {
+					| Ok(length) => length,
+					| Err(_) => {
+						return Response::error(400, "Malformed Content-Length header")
+							.write_to(&mut stream);
+					},
+				};
+			}
+		}
+	}
+
+	if content_length > MAX_BODY_BYTES {
+		return Response::error(
+			413,
+			&format!("Request body is limited to {} bytes", MAX_BODY_BYTES),
+		)
+		.write_to(&mut stream);
+	}
+
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body)?;
+
+	respond(method, target, &body, state).write_to(&mut stream)
+}
+
+/// Handles connections from `queue` until the server stops.
+fn work(
+	queue: &Mutex<Receiver<TcpStream>>,
+	state: &State,
+) {
+	loop {
+		// the lock is only held while waiting, so the other workers can handle connections
+		let stream = match queue.lock().unwrap().recv() {
+			| Ok(stream) => stream,
+			| Err(_) => return,
+		};
+		if let Err(e) = handle_connection(stream, state) {
+			println!("\x1b[31mConnection error:\x1b[0m {}", e);
+		}
+	}
+}
+
+fn serve(
+	listener: TcpListener,
+	state: Arc<State>,
+) {
+	let (sender, receiver) = sync_channel(QUEUE_LENGTH);
+	let receiver = Arc::new(Mutex::new(receiver));
+	for _ in 0..WORKERS {
+		let receiver = receiver.clone();
+		let state = state.clone();
+		thread::spawn(move || work(&receiver, &state));
+	}
+
+	for stream in listener.incoming() {
+		match stream {
+			// blocks while every worker is busy and the queue is full
+			| Ok(stream) => {
+				if sender.send(stream).is_err() {
+					println!("\x1b[31mEvery worker has stopped, shutting down the server\x1b[0m");
+					return;
+				}
+			},
+			| Err(e) => println!("\x1b[31mFailed to accept connection:\x1b[0m {}", e),
+		}
+	}
+}
+
+pub fn start() {
+	let port = input(
+		&format!("\nEnter the port to listen on (default {}):", DEFAULT_PORT),
+		true,
+	)
+	.parse::<u16>()
+	.unwrap_or(DEFAULT_PORT);
+
+	let listener = match TcpListener::bind(("127.0.0.1", port)) {
+		| Ok(listener) => listener,
+		| Err(e) => {
+			println!("\x1b[31mFailed to bind to port {}:\x1b[0m {}", port, e);
+			return main();
+		},
+	};
+
+	let state = Arc::new(State {
+		fonts:      load_fonts(),
+		background: load_background(),
+		cache:      Mutex::new(RenderCache::new(CACHE_CAPACITY)),
+	});
+
+	println!(
+		"\n\x1b[32mListening on http://127.0.0.1:{}\x1b[0m\n\
+        \nGET  /render?text=&aHello&layout=chat&scale=2\
+        \nPOST /render {{\"text\": \"&aHello\", \"layout\": \"chat\", \"scale\": 2}}\n\
+        \nPress Ctrl+C to stop the server",
+		port
+	);
+	serve(listener, state);
+
+	main()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn state() -> State {
+		State {
+			fonts:      load_fonts(),
+			background: load_background(),
+			cache:      Mutex::new(RenderCache::new(2)),
+		}
+	}
+
+	#[test]
+	fn decodes_query_parameters() {
+		let request = parse_query("text=%26aHello+world%C2%A7&layout=chat&scale=3").unwrap();
+		assert_eq!(request.text, "&aHello world§");
+		assert_eq!(request.layout, Layout::Chat);
+		assert_eq!(request.scale, Some(3));
+
+		assert!(parse_query("layout=chat").is_err());
+		assert!(parse_query("text=a&layout=sign").is_err());
+		assert!(parse_query("text=%zz").is_err());
+	}
+
+	#[test]
+	fn cache_evicts_least_recently_used() {
+		let mut cache = RenderCache::new(2);
+		let key = |text: &str| (text.to_string(), Layout::Chat, 2);
+
+		cache.insert(key("a"), Arc::new(vec![1]));
+		cache.insert(key("b"), Arc::new(vec![2]));
+		assert!(cache.get(&key("a")).is_some());
+		cache.insert(key("c"), Arc::new(vec![3]));
+
+		assert!(cache.get(&key("a")).is_some());
+		assert!(cache.get(&key("b")).is_none());
+		assert!(cache.get(&key("c")).is_some());
+	}
+
+	#[test]
+	fn renders_png_for_get_and_post() {
+		let state = state();
+
+		let response = respond("GET", "/render?text=%26cHi&layout=chat", &[], &state);
+		assert_eq!(response.status, 200);
+		assert!(response.body.starts_with(b"\x89PNG"));
+
+		let body = br#"{"text": "&cHi", "layout": "chat"}"#;
+		let cached = respond("POST", "/render", body, &state);
+		assert!(Arc::ptr_eq(&response.body, &cached.body));
+	}
+
+	#[test]
+	fn rejects_bad_requests() {
+		let state = state();
+		let long_text = format!("/render?text={}", "a".repeat(MAX_TEXT_CHARS + 1));
+
+		assert_eq!(respond("GET", "/missing", &[], &state).status, 404);
+		assert_eq!(respond("DELETE", "/render", &[], &state).status, 405);
+		assert_eq!(respond("POST", "/render", b"{", &state).status, 400);
+		assert_eq!(
+			respond("GET", "/render?text=a&scale=0", &[], &state).status,
+			400
+		);
+		assert_eq!(respond("GET", &long_text, &[], &state).status, 413);
+	}
+
+	#[test]
+	fn limits_image_size() {
+		let state = state();
+
+		// the chat box stops growing at the height of the screen
+		let many_lines = format!("/render?layout=chat&scale=8&text={}", "\\n".repeat(1000));
+		assert_eq!(respond("GET", &many_lines, &[], &state).status, 200);
+
+		let long_line = format!(
+			"/render?layout=tooltip&scale=8&text={}",
+			"a".repeat(MAX_TEXT_CHARS)
+		);
+		assert_eq!(respond("GET", &long_line, &[], &state).status, 413);
+	}
+
+	#[test]
+	fn rejects_malformed_content_length() {
+		let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || serve(listener, Arc::new(state())));
+
+		let mut stream = TcpStream::connect(address).unwrap();
+		stream
+			.write_all(b"POST /render HTTP/1.1\r\nContent-Length: ten\r\n\r\n{}")
+			.unwrap();
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response).unwrap();
+		assert!(response.starts_with(b"HTTP/1.1 400 Bad Request"));
+	}
+
+	#[test]
+	fn workers_handle_more_connections_than_there_are_workers() {
+		let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || serve(listener, Arc::new(state())));
+
+		let clients: Vec<_> = (0..WORKERS * 2)
+			.map(|_| {
+				thread::spawn(move || {
+					let mut stream = TcpStream::connect(address).unwrap();
+					stream
+						.write_all(b"GET /render?text=Hi HTTP/1.1\r\n\r\n")
+						.unwrap();
+					let mut response = Vec::new();
+					stream.read_to_end(&mut response).unwrap();
+					response
+				})
+			})
+			.collect();
+
+		for client in clients {
+			assert!(client.join().unwrap().starts_with(b"HTTP/1.1 200 OK"));
+		}
+	}
+}
@@ -0,0 +1,345 @@
+use serde_json::{Map, Number, Value};
+
+/// A parsed SNBT value. Numbers lose their type suffix since only their value matters for
+/// tooltips, and arrays like `[I; 1, 2]` are read as lists.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+	Number(f64),
+	String(String),
+	List(Vec<Tag>),
+	Compound(Vec<(String, Tag)>),
+}
+
+impl Tag {
+	pub fn get(
+		&self,
+		key: &str,
+	) -> Option<&Tag> {
+		match self {
+			| Tag::Compound(entries) => entries
+				.iter()
+				.find(|(name, _)| name == key)
+				.map(|(_, tag)| tag),
+			| _ => None,
+		}
+	}
+
+	pub fn as_f64(&self) -> Option<f64> {
+		match self {
+			| Tag::Number(n) => Some(*n),
+			| _ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			| Tag::String(s) => Some(s),
+			| _ => None,
+		}
+	}
+
+	pub fn as_list(&self) -> Option<&[Tag]> {
+		match self {
+			| Tag::List(items) => Some(items),
+			| _ => None,
+		}
+	}
+
+	pub fn as_compound(&self) -> Option<&[(String, Tag)]> {
+		match self {
+			| Tag::Compound(entries) => Some(entries),
+			| _ => None,
+		}
+	}
+
+	/// Bytes used as booleans, `true` and `false` are parsed as 1 and 0.
+	pub fn as_bool(&self) -> Option<bool> { self.as_f64().map(|n| n != 0.0) }
+
+	/// Converts to JSON, for text components written in SNBT rather than as a JSON string.
+	pub fn to_json(&self) -> Value {
+		match self {
+			| Tag::Number(n) => Number::from_f64(*n).map_or(Value::Null, Value::Number),
+			| Tag::String(s) => Value::String(s.clone()),
+			| Tag::List(items) => Value::Array(items.iter().map(Tag::to_json).collect()),
+			| Tag::Compound(entries) => Value::Object(
+				entries
+					.iter()
+					.map(|(key, tag)| (key.clone(), tag.to_json()))
+					.collect::<Map<_, _>>(),
+			),
+		}
+	}
+}
+
+/// A cursor over SNBT text, shared with the `/give` command parser.
+pub struct Reader {
+	chars: Vec<char>,
+	pos:   usize,
+}
+
+impl Reader {
+	pub fn new(text: &str) -> Self {
+		Reader {
+			chars: text.chars().collect(),
+			pos:   0,
+		}
+	}
+
+	pub fn peek(&self) -> Option<char> { self.chars.get(self.pos).copied() }
+
+	pub fn skip_whitespace(&mut self) {
+		while self.peek().is_some_and(char::is_whitespace) {
+			self.pos += 1;
+		}
+	}
+
+	pub fn rest(&self) -> String { self.chars[self.pos..].iter().collect() }
+
+	fn error(
+		&self,
+		message: &str,
+	) -> String {
+		format!("{} at character {}", message, self.pos + 1)
+	}
+
+	pub fn expect(
+		&mut self,
+		expected: char,
+	) -> Result<(), String> {
+		self.skip_whitespace();
+		if self.peek() == Some(expected) {
+			self.pos += 1;
+			Ok(())
+		} else {
+			Err(self.error(&format!("Expected '{}'", expected)))
+		}
+	}
+
+	/// Consumes `expected` if it is next, skipping whitespace before it.
+	pub fn accept(
+		&mut self,
+		expected: char,
+	) -> bool {
+		self.skip_whitespace();
+		let found = self.peek() == Some(expected);
+		if found {
+			self.pos += 1;
+		}
+		found
+	}
+
+	/// Reads characters until whitespace or one of `stops`.
+	pub fn read_until(
+		&mut self,
+		stops: &[char],
+	) -> String {
+		let start = self.pos;
+		while self
+			.peek()
+			.is_some_and(|c| !c.is_whitespace() && !stops.contains(&c))
+		{
+			self.pos += 1;
+		}
+		self.chars[start..self.pos].iter().collect()
+	}
+
+	/// Skips a `[...]` section and everything nested in it, if one is next.
+	pub fn skip_brackets(&mut self) {
+		if self.peek() != Some('[') {
+			return;
+		}
+
+		let mut depth = 0;
+		while let Some(c) = self.peek() {
+			self.pos += 1;
+			match c {
+				| '[' => depth += 1,
+				| ']' => {
+					depth -= 1;
+					if depth == 0 {
+						return;
+					}
+				},
+				| _ => {},
+			}
+		}
+	}
+
+	fn read_quoted(&mut self) -> Result<String, String> {
+		let quote = self.chars[self.pos];
+		self.pos += 1;
+		let mut value = String::new();
+
+		while let Some(c) = self.peek() {
+			self.pos += 1;
+			match c {
+				| '\\' => match self.peek() {
+					| Some(escaped) => {
+						value.push(escaped);
+						self.pos += 1;
+					},
+					| None => break,
+				},
+				| _ if c == quote => return Ok(value),
+				| _ => value.push(c),
+			}
+		}
+
+		Err(self.error("Unterminated string"))
+	}
+
+	/// Reads a quoted or unquoted key that is followed by `separator`.
+	pub fn read_key(
+		&mut self,
+		separator: char,
+	) -> Result<String, String> {
+		self.skip_whitespace();
+		match self.peek() {
+			| Some('"' | '\'') => self.read_quoted(),
+			| _ => {
+				let key = self.read_until(&[separator, ',', '{', '}', '[', ']']);
+				if key.is_empty() {
+					Err(self.error("Expected a key"))
+				} else {
+					Ok(key)
+				}
+			},
+		}
+	}
+
+	pub fn read_tag(&mut self) -> Result<Tag, String> {
+		self.skip_whitespace();
+		match self.peek() {
+			| Some('{') => self.read_compound(),
+			| Some('[') => self.read_list(),
+			| Some('"' | '\'') => self.read_quoted().map(Tag::String),
+			| Some(_) => {
+				let value = self.read_until(&[',', '}', ']']);
+				Ok(unquoted(&value))
+			},
+			| None => Err(self.error("Expected a value")),
+		}
+	}
+
+	fn read_compound(&mut self) -> Result<Tag, String> {
+		self.expect('{')?;
+		let mut entries = Vec::new();
+
+		if self.accept('}') {
+			return Ok(Tag::Compound(entries));
+		}
+
+		loop {
+			let key = self.read_key(':')?;
+			self.expect(':')?;
+			entries.push((key, self.read_tag()?));
+
+			if self.accept('}') {
+				return Ok(Tag::Compound(entries));
+			}
+			self.expect(',')?;
+		}
+	}
+
+	fn read_list(&mut self) -> Result<Tag, String> {
+		self.expect('[')?;
+		let mut items = Vec::new();
+
+		// typed arrays, e.g. [I; 1, 2, 3]
+		self.skip_whitespace();
+		if matches!(self.peek(), Some('B' | 'I' | 'L'))
+			&& self.chars.get(self.pos + 1) == Some(&';')
+		{
+			self.pos += 2;
+		}
+
+		if self.accept(']') {
+			return Ok(Tag::List(items));
+		}
+
+		loop {
+			items.push(self.read_tag()?);
+
+			if self.accept(']') {
+				return Ok(Tag::List(items));
+			}
+			self.expect(',')?;
+		}
+	}
+}
+
+fn unquoted(value: &str) -> Tag {
+	match value {
+		| "true" => return Tag::Number(1.0),
+		| "false" => return Tag::Number(0.0),
+		| _ => {},
+	}
+
+	let number = value
+		.strip_suffix(['b', 'B', 's', 'S', 'l', 'L', 'f', 'F', 'd', 'D'])
+		.unwrap_or(value);
+	let numeric = number
+		.chars()
+		.all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+	match number.parse::<f64>() {
+		| Ok(n) if numeric => Tag::Number(n),
+		| _ => Tag::String(value.to_string()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(text: &str) -> Result<Tag, String> {
+		let mut reader = Reader::new(text);
+		let tag = reader.read_tag()?;
+		reader.skip_whitespace();
+		if reader.peek().is_some() {
+			return Err(reader.error("Unexpected trailing text"));
+		}
+		Ok(tag)
+	}
+
+	#[test]
+	fn parses_nested_values() {
+		let tag = parse(
+			r#"{display:{Name:'{"text":"Hi"}',Lore:["a",'b']},Enchantments:[{id:"minecraft:sharpness",lvl:5s}],HideFlags:2,Unbreakable:1b,ids:[I;1,2]}"#,
+		)
+		.unwrap();
+
+		let display = tag.get("display").unwrap();
+		assert_eq!(
+			display.get("Name").unwrap().as_str(),
+			Some(r#"{"text":"Hi"}"#)
+		);
+		assert_eq!(display.get("Lore").unwrap().as_list().unwrap().len(), 2);
+
+		let enchantment = &tag.get("Enchantments").unwrap().as_list().unwrap()[0];
+		assert_eq!(
+			enchantment.get("id").unwrap().as_str(),
+			Some("minecraft:sharpness")
+		);
+		assert_eq!(enchantment.get("lvl").unwrap().as_f64(), Some(5.0));
+		assert_eq!(tag.get("Unbreakable").unwrap().as_bool(), Some(true));
+		assert_eq!(tag.get("ids").unwrap().as_list().unwrap().len(), 2);
+	}
+
+	#[test]
+	fn reads_unquoted_values() {
+		assert_eq!(parse("1.5f").unwrap(), Tag::Number(1.5));
+		assert_eq!(parse("true").unwrap(), Tag::Number(1.0));
+		assert_eq!(
+			parse("add_value").unwrap(),
+			Tag::String("add_value".to_string())
+		);
+		assert_eq!(parse("b").unwrap(), Tag::String("b".to_string()));
+	}
+
+	#[test]
+	fn reports_errors() {
+		assert!(parse("{a:1").is_err());
+		assert!(parse("{a:'1}").is_err());
+		assert!(parse("{a:1} b").is_err());
+	}
+}
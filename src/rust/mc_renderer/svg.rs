@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use image::{Rgb, RgbaImage};
+use rusttype::{Font, OutlineBuilder, Scale, point};
+
+use super::align::split_alignments;
+use super::head::{head_index, is_head_char};
+use super::renderer::Renderer;
+use super::{
+	Decoration,
+	Style,
+	advance,
+	decorations,
+	format_number,
+	line_width,
+	parse_text_with,
+	shadow_line_with,
+};
+
+/// How glyphs are written into the SVG.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvgMode {
+	/// Vector outlines taken from the bundled fonts, sharp at any size.
+	Outline,
+	/// One rectangle per run of rasterized pixels, matching the PNG output exactly.
+	Pixel,
+}
+
+/// Writes a glyph outline as SVG path data, offset to the glyph's position.
+struct PathData {
+	d:      String,
+	offset: (f32, f32),
+}
+
+impl PathData {
+	fn point(
+		&mut self,
+		x: f32,
+		y: f32,
+	) {
+		write!(
+			self.d,
+			"{} {} ",
+			format_number(x + self.offset.0),
+			format_number(y + self.offset.1)
+		)
+		.unwrap();
+	}
+}
+
+impl OutlineBuilder for PathData {
+	fn move_to(
+		&mut self,
+		x: f32,
+		y: f32,
+	) {
+		self.d.push('M');
+		self.point(x, y);
+	}
+
+	fn line_to(
+		&mut self,
+		x: f32,
+		y: f32,
+	) {
+		self.d.push('L');
+		self.point(x, y);
+	}
+
+	fn quad_to(
+		&mut self,
+		x1: f32,
+		y1: f32,
+		x: f32,
+		y: f32,
+	) {
+		self.d.push('Q');
+		self.point(x1, y1);
+		self.point(x, y);
+	}
+
+	fn curve_to(
+		&mut self,
+		x1: f32,
+		y1: f32,
+		x2: f32,
+		y2: f32,
+		x: f32,
+		y: f32,
+	) {
+		self.d.push('C');
+		self.point(x1, y1);
+		self.point(x2, y2);
+		self.point(x, y);
+	}
+
+	fn close(&mut self) { self.d.push('Z'); }
+}
+
+fn hex(color: Rgb<u8>) -> String { format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2]) }
+
+fn decoration(
+	svg: &mut String,
+	decoration: Decoration,
+	thickness: f32,
+) {
+	writeln!(
+		svg,
+		r#"<rect class="{}" x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+		decoration.kind,
+		format_number(decoration.start),
+		format_number(decoration.top),
+		format_number(decoration.end - decoration.start),
+		format_number(thickness),
+		hex(decoration.color)
+	)
+	.unwrap();
+}
+
+/// The document being written and the settings shared by every glyph in it.
+struct Canvas<'a> {
+	svg:   String,
+	fonts: &'a HashMap<&'a str, Font<'a>>,
+	scale: Scale,
+	mode:  SvgMode,
+	heads: &'a [RgbaImage],
+}
+
+impl Canvas<'_> {
+	fn draw_character(
+		&mut self,
+		c: char,
+		x: &mut f32,
+		y: f32,
+		style: Style,
+	) {
+		if is_head_char(c) {
+			*x += advance(c, style, self.fonts, self.scale);
+			return;
+		}
+
+		let svg = &mut self.svg;
+		let scaled_glyph = self.fonts[style.font_key()].glyph(c).scaled(self.scale);
+		let positioned_glyph = scaled_glyph.clone().positioned(point(*x, y));
+
+		if let Some(bounding_box) = positioned_glyph.pixel_bounding_box() {
+			match self.mode {
+				| SvgMode::Outline => {
+					let mut path = PathData {
+						d:      String::new(),
+						offset: (*x, y),
+					};
+					if scaled_glyph.build_outline(&mut path) {
+						writeln!(
+							svg,
+							r#"<path d="{}" fill="{}"/>"#,
+							path.d.trim_end(),
+							hex(style.color)
+						)
+						.unwrap();
+					}
+				},
+				| SvgMode::Pixel => {
+					let width = bounding_box.width() as usize;
+					let mut filled = vec![false; width * bounding_box.height() as usize];
+					positioned_glyph.draw(|dx, dy, v| {
+						filled[dy as usize * width + dx as usize] = v >= 0.5;
+					});
+
+					writeln!(svg, r#"<g fill="{}">"#, hex(style.color)).unwrap();
+					for (row, pixels) in filled.chunks(width).enumerate() {
+						let mut column = 0;
+						while column < width {
+							if !pixels[column] {
+								column += 1;
+								continue;
+							}
+							let start = column;
+							while column < width && pixels[column] {
+								column += 1;
+							}
+							writeln!(
+								svg,
+								r#"<rect x="{}" y="{}" width="{}" height="1"/>"#,
+								bounding_box.min.x + start as i32,
+								bounding_box.min.y + row as i32,
+								column - start
+							)
+							.unwrap();
+						}
+					}
+					svg.push_str("</g>\n");
+				},
+			}
+		}
+
+		*x += scaled_glyph.h_metrics().advance_width;
+	}
+
+	fn draw_line(
+		&mut self,
+		line: &[(char, Style)],
+		origin: (f32, f32),
+	) {
+		let mut x = origin.0;
+		for &(c, style) in line {
+			self.draw_character(c, &mut x, origin.1, style);
+		}
+
+		for run in decorations(line, self.fonts, self.scale, origin) {
+			decoration(&mut self.svg, run, self.scale.y / 8.0);
+		}
+	}
+
+	/// Draws the heads standing in `line` as one rectangle per skin pixel, where the PNG output
+	/// draws them.
+	fn draw_heads(
+		&mut self,
+		line: &[(char, Style)],
+		origin: (f32, f32),
+	) {
+		let unit = self.scale.y / 8.0;
+		let mut x = origin.0;
+
+		for &(c, style) in line {
+			if let Some(head) = head_index(c).and_then(|index| self.heads.get(index)) {
+				let top = origin.1 - 7.0 * unit;
+				let pixel = format_number(unit);
+				self.svg.push_str("<g class=\"head\">\n");
+				for (px, py, color) in head.enumerate_pixels() {
+					if color[3] == 0 {
+						continue;
+					}
+					writeln!(
+						self.svg,
+						r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+						format_number(x + px as f32 * unit),
+						format_number(top + py as f32 * unit),
+						pixel,
+						pixel,
+						hex(Rgb([color[0], color[1], color[2]]))
+					)
+					.unwrap();
+				}
+				self.svg.push_str("</g>\n");
+			}
+			x += advance(c, style, self.fonts, self.scale);
+		}
+	}
+}
+
+/// Renders `text` with the font, scale, margins, line height, shadow, palette, alignment and skins
+/// of `renderer` to an
+/// SVG document sized to fit it, with a transparent background. Underlines and strikethroughs
+/// are written as rectangles with the class of their kind, heads as groups of pixel rectangles
+/// with the `head` class, and lines are aligned to the widest one.
+pub fn render_svg(
+	text: &str,
+	renderer: &Renderer,
+	mode: SvgMode,
+) -> String {
+	let fonts = renderer.fonts();
+	let scale = renderer.scale();
+	let palette = renderer.palette();
+	let (text, heads) = renderer.inline_heads(text);
+	let (text, alignments) = split_alignments(&text, renderer.align());
+	let lines = parse_text_with(&text, palette);
+	let line_height = scale.y * renderer.line_height();
+	let margins = renderer.margins();
+	let ascent = fonts["regular"].v_metrics(scale).ascent;
+
+	let widths: Vec<f32> = lines
+		.iter()
+		.map(|line| line_width(line, fonts, scale))
+		.collect();
+	let text_width = widths.iter().copied().fold(0.0, f32::max);
+	let width = (text_width + margins.0 * 2.0).ceil();
+	let height = (lines.len() as f32 * line_height + margins.1 * 2.0).ceil();
+
+	let mut svg = format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} \
+		 {h}\">\n",
+		w = width,
+		h = height
+	);
+	if mode == SvgMode::Pixel {
+		svg.push_str("<g shape-rendering=\"crispEdges\">\n");
+	} else {
+		svg.push_str("<g>\n");
+	}
+
+	let mut canvas = Canvas {
+		svg,
+		fonts,
+		scale,
+		mode,
+		heads: &heads,
+	};
+	let unit = scale.y / 8.0;
+	for (i, line) in lines.iter().enumerate() {
+		let (x, y) = (
+			margins.0 + alignments[i].offset(widths[i], text_width, unit),
+			margins.1 + ascent + i as f32 * line_height,
+		);
+
+		if renderer.shadow() {
+			canvas.draw_line(&shadow_line_with(line, palette), (x + unit, y + unit));
+		}
+		canvas.draw_line(line, (x, y));
+		canvas.draw_heads(line, (x, y));
+	}
+
+	canvas.svg.push_str("</g>\n</svg>\n");
+	canvas.svg
+}
+
+#[cfg(test)]
+mod tests {
+	use image::Rgba;
+
+	use super::*;
+	use crate::stuff::TempPath;
+
+	#[test]
+	fn outlines_keep_colors() {
+		let svg = render_svg("&cA&9B", &Renderer::new(), SvgMode::Outline);
+		assert!(svg.starts_with("<svg"));
+		assert_eq!(svg.matches("<path").count(), 2);
+		assert!(svg.contains(r##"fill="#ff5555""##));
+		assert!(svg.contains(r##"fill="#5555ff""##));
+	}
+
+	#[test]
+	fn pixel_mode_uses_rectangles() {
+		let svg = render_svg("A", &Renderer::new(), SvgMode::Pixel);
+		assert!(!svg.contains("<path"));
+		assert!(svg.contains("<rect"));
+	}
+
+	#[test]
+	fn decorations_are_separate_elements() {
+		let svg = render_svg("&n&mAB", &Renderer::new(), SvgMode::Outline);
+		assert_eq!(svg.matches(r#"class="underline""#).count(), 1);
+		assert_eq!(svg.matches(r#"class="strikethrough""#).count(), 1);
+	}
+
+	#[test]
+	fn shadow_is_drawn_first() {
+		let svg = render_svg("&fA", &Renderer::new().with_shadow(true), SvgMode::Outline);
+		let shadow = svg.find(r##"fill="#3f3f3f""##).unwrap();
+		let text = svg.find(r##"fill="#ffffff""##).unwrap();
+		assert!(shadow < text);
+	}
+
+	#[test]
+	fn uses_the_renderer_margins_and_line_height() {
+		let renderer = Renderer::new().with_margins(0.0, 3.0).with_line_height(1.5);
+		let svg = render_svg("a\\nb", &renderer, SvgMode::Outline);
+		// two lines of 16 * 1.5 pixels between the vertical margins
+		assert!(svg.contains(r#"height="54""#), "{}", svg);
+	}
+
+	#[test]
+	fn heads_are_drawn_like_the_png() {
+		let skins = TempPath::new("svg_heads_are_drawn_like_the_png");
+		std::fs::create_dir_all(&skins.0).unwrap();
+		RgbaImage::from_pixel(64, 64, Rgba([0, 200, 0, 255]))
+			.save(skins.0.join("Steve.png"))
+			.unwrap();
+		let renderer = Renderer::new().with_skins(Some(skins.0.clone()));
+
+		let svg = render_svg("{head:Steve} \\{head:Steve}", &renderer, SvgMode::Outline);
+		assert_eq!(svg.matches(r#"<g class="head">"#).count(), 1);
+		assert_eq!(svg.matches(r##"fill="#00c800""##).count(), 64);
+		// the escaped placeholder is written as text, its braces included
+		assert_eq!(svg.matches("<path").count(), "{head:Steve}".len());
+	}
+}
@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use image::{Rgb, RgbImage, RgbaImage};
+use rusttype::{Font, Scale};
+
+use super::head::{draw_head, load_head};
+use super::palette::Palette;
+use super::{
+	backdrop,
+	blend_rect,
+	draw_line,
+	line_width,
+	load_background,
+	load_fonts,
+	parse_text_with,
+	prompt_palette,
+	prompt_save,
+	shadow_line_with,
+};
+use crate::main;
+use crate::stuff::input;
+
+/// Vanilla starts a new column once a column would have more rows than this.
+const MAX_ROWS: usize = 20;
+/// The most players vanilla lists.
+const MAX_ENTRIES: usize = 80;
+const COLUMN_GAP: i64 = 5;
+const ROW_HEIGHT: i64 = 9;
+/// Width of the ping icon and the pixel of space before it.
+const PING_WIDTH: i64 = 13;
+
+/// A player in the list, `prefix` and `suffix` being their team's.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TabEntry {
+	pub prefix: String,
+	pub name:   String,
+	pub suffix: String,
+	/// Latency in milliseconds, negative while the player hasn't sent one yet.
+	pub ping:   i32,
+}
+
+impl TabEntry {
+	fn text(&self) -> String { format!("{}{}&r{}", self.prefix, self.name, self.suffix) }
+
+	/// Bars lit on the ping icon, using vanilla's thresholds.
+	pub fn ping_bars(&self) -> Option<u8> {
+		match self.ping {
+			| ..0 => None,
+			| 0..150 => Some(5),
+			| 150..300 => Some(4),
+			| 300..600 => Some(3),
+			| 600..1000 => Some(2),
+			| _ => Some(1),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TabList {
+	pub header:  String,
+	pub footer:  String,
+	pub entries: Vec<TabEntry>,
+}
+
+/// Reads a tab list description:
+///
+/// ```text
+/// header: &6&lMy Server\n&7play.example.com
+/// footer: &7Players online: 3
+/// Steve|42
+/// &c[Admin] |Alex| &7AFK|180
+/// Notch
+/// ```
+///
+/// Players are `name`, `name|ping` or `prefix|name|suffix|ping`, listed in the order given.
+/// Repeated `header:` and `footer:` lines add lines, and lines starting with `#` are comments.
+pub fn parse_tab_list(source: &str) -> Result<TabList, String> {
+	let mut tab_list = TabList::default();
+
+	for (number, line) in source.lines().enumerate() {
+		if line.trim().is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		if let Some(header) = line.strip_prefix("header:") {
+			append_line(&mut tab_list.header, header.trim_start());
+			continue;
+		}
+		if let Some(footer) = line.strip_prefix("footer:") {
+			append_line(&mut tab_list.footer, footer.trim_start());
+			continue;
+		}
+
+		let parts: Vec<&str> = line.split('|').collect();
+		let ping = |text: &str| {
+			text.trim()
+				.parse::<i32>()
+				.map_err(|_| format!("Line {}: '{}' is not a ping", number + 1, text.trim()))
+		};
+		let entry = match parts.as_slice() {
+			| [name] => TabEntry {
+				name: name.trim().to_string(),
+				..Default::default()
+			},
+			| [name, latency] => TabEntry {
+				name: name.trim().to_string(),
+				ping: ping(latency)?,
+				..Default::default()
+			},
+			| [prefix, name, suffix, latency] => TabEntry {
+				prefix: prefix.to_string(),
+				name:   name.trim().to_string(),
+				suffix: suffix.to_string(),
+				ping:   ping(latency)?,
+			},
+			| _ => {
+				return Err(format!(
+					"Line {}: expected name, name|ping or prefix|name|suffix|ping",
+					number + 1
+				));
+			},
+		};
+		tab_list.entries.push(entry);
+	}
+
+	Ok(tab_list)
+}
+
+fn append_line(
+	text: &mut String,
+	line: &str,
+) {
+	if !text.is_empty() {
+		text.push_str("\\n");
+	}
+	text.push_str(line);
+}
+
+/// Rows and columns vanilla splits `count` players into.
+pub fn columns(count: usize) -> (usize, usize) {
+	let mut columns = 1;
+	let mut rows = count;
+	while rows > MAX_ROWS {
+		columns += 1;
+		rows = count.div_ceil(columns);
+	}
+	(rows, columns)
+}
+
+/// Draws the ping icon's five bars into its 10x8 area, taller to the right.
+fn draw_ping(
+	image: &mut RgbImage,
+	(left, top): (i64, i64),
+	bars: Option<u8>,
+	gui_scale: u32,
+) {
+	for bar in 0..5 {
+		let height = bar + 2 + bar / 2;
+		let color = match bars {
+			| Some(lit) if bar < lit as i64 => Rgb([0, 224, 0]),
+			| Some(_) => Rgb([56, 56, 56]),
+			// no ping yet
+			| None => Rgb([170, 0, 0]),
+		};
+		let x = left + bar * 2;
+		fill(
+			image,
+			(x, top + 8 - height),
+			(x + 1, top + 8),
+			color,
+			1.0,
+			gui_scale,
+		);
+	}
+}
+
+/// Blends a rectangle given in GUI pixels.
+fn fill(
+	image: &mut RgbImage,
+	(x0, y0): (i64, i64),
+	(x1, y1): (i64, i64),
+	color: Rgb<u8>,
+	alpha: f32,
+	gui_scale: u32,
+) {
+	let pixels = |value: i64| (value.max(0) as u32) * gui_scale;
+	blend_rect(
+		image,
+		(pixels(x0), pixels(y0)),
+		(pixels(x1), pixels(y1)),
+		color,
+		alpha,
+	);
+}
+
+/// Renders `tab_list` over the background the way vanilla lays out the player list. Heads are
+/// shown when `heads` is given, like on online mode servers, for the players it has.
+pub fn render_tab_list(
+	tab_list: &TabList,
+	heads: Option<&HashMap<String, RgbaImage>>,
+	fonts: &HashMap<&str, Font>,
+	background: &RgbImage,
+	gui_scale: u32,
+	palette: &Palette,
+) -> RgbImage {
+	let unit = gui_scale as f32;
+	let scale = Scale::uniform(8.0 * unit);
+	let width_of = |line: &[(char, _)]| (line_width(line, fonts, scale) / unit).ceil() as i64;
+
+	let entries = &tab_list.entries[..tab_list.entries.len().min(MAX_ENTRIES)];
+	let header = if tab_list.header.is_empty() {
+		Vec::new()
+	} else {
+		parse_text_with(&tab_list.header, palette)
+	};
+	let footer = if tab_list.footer.is_empty() {
+		Vec::new()
+	} else {
+		parse_text_with(&tab_list.footer, palette)
+	};
+	let names: Vec<_> = entries
+		.iter()
+		.map(|entry| parse_text_with(&entry.text(), palette).swap_remove(0))
+		.collect();
+
+	// the screenshot was taken at gui scale 2, so it is half as many gui pixels
+	let screen_width = background.width() as i64 / 2;
+	let (rows, column_count) = columns(entries.len());
+	let head_width = if heads.is_some() { 9 } else { 0 };
+	let name_width = names.iter().map(|name| width_of(name)).max().unwrap_or(0);
+	// screens narrower than vanilla's 50 pixel border still get empty slots, not negative ones
+	let slot_width = (column_count as i64 * (head_width + name_width + PING_WIDTH))
+		.min((screen_width - 50).max(0))
+		/ column_count as i64;
+	let entries_width = slot_width * column_count as i64 + (column_count as i64 - 1) * COLUMN_GAP;
+	let total_width = header
+		.iter()
+		.chain(&footer)
+		.map(|line| width_of(line))
+		.fold(entries_width, i64::max);
+
+	let center = screen_width / 2;
+	let (left, right) = (center - total_width / 2 - 1, center + total_width / 2 + 1);
+	let entries_left = center - entries_width / 2;
+	let height = 10 + (header.len() + rows + footer.len()) as i64 * ROW_HEIGHT + 12;
+	let mut image = backdrop(
+		background,
+		gui_scale,
+		(0, 0),
+		(
+			background.width() * gui_scale / 2,
+			(background.height() as i64 / 2).max(height) as u32 * gui_scale,
+		),
+	);
+
+	let text = |image: &mut RgbImage, line: &[(char, _)], (x, y): (i64, i64)| {
+		let (x, y) = (x as f32 * unit, (y + 7) as f32 * unit);
+		draw_line(
+			&shadow_line_with(line, palette),
+			fonts,
+			image,
+			(x + unit, y + unit),
+			scale,
+		);
+		draw_line(line, fonts, image, (x, y), scale);
+	};
+	let black = Rgb([0, 0, 0]);
+
+	let mut top = 10;
+	if !header.is_empty() {
+		let bottom = top + header.len() as i64 * ROW_HEIGHT;
+		fill(
+			&mut image,
+			(left, top - 1),
+			(right, bottom),
+			black,
+			0.5,
+			gui_scale,
+		);
+		for line in &header {
+			text(&mut image, line, (center - width_of(line) / 2, top));
+			top += ROW_HEIGHT;
+		}
+		top += 1;
+	}
+
+	let bottom = top + rows as i64 * ROW_HEIGHT;
+	fill(
+		&mut image,
+		(left, top - 1),
+		(right, bottom),
+		black,
+		0.5,
+		gui_scale,
+	);
+	for (i, (entry, name)) in entries.iter().zip(&names).enumerate() {
+		let (column, row) = ((i / rows) as i64, (i % rows) as i64);
+		let x = entries_left + column * (slot_width + COLUMN_GAP);
+		let y = top + row * ROW_HEIGHT;
+		let white = Rgb([255, 255, 255]);
+		fill(
+			&mut image,
+			(x, y),
+			(x + slot_width, y + 8),
+			white,
+			0.125,
+			gui_scale,
+		);
+
+		let mut name_left = x;
+		if let Some(heads) = heads {
+			if let Some(head) = heads.get(&entry.name) {
+				let position = (x * gui_scale as i64, y * gui_scale as i64);
+				draw_head(&mut image, head, position, 8 * gui_scale);
+			}
+			name_left += 9;
+		}
+		text(&mut image, name, (name_left, y));
+		draw_ping(
+			&mut image,
+			(x + slot_width - 11, y),
+			entry.ping_bars(),
+			gui_scale,
+		);
+	}
+
+	if !footer.is_empty() {
+		top = bottom + 1;
+		let bottom = top + footer.len() as i64 * ROW_HEIGHT;
+		fill(
+			&mut image,
+			(left, top - 1),
+			(right, bottom),
+			black,
+			0.5,
+			gui_scale,
+		);
+		for line in &footer {
+			text(&mut image, line, (center - width_of(line) / 2, top));
+			top += ROW_HEIGHT;
+		}
+	}
+
+	image
+}
+
+pub fn start() {
+	let path = input("\nPath to the tab list file:", true);
+	let tab_list = match fs::read_to_string(&path)
+		.map_err(|e| format!("Failed to read {}: {}", path, e))
+		.and_then(|source| parse_tab_list(&source))
+	{
+		| Ok(tab_list) => tab_list,
+		| Err(e) => {
+			println!("\x1b[31m{}\x1b[0m", e);
+			return main();
+		},
+	};
+	let palette = prompt_palette();
+
+	let skins = input(
+		"\nFolder with <name>.png skins for player heads (leave empty for no heads):",
+		true,
+	);
+	let heads = (!skins.is_empty()).then(|| {
+		tab_list
+			.entries
+			.iter()
+			.filter_map(|entry| {
+				let head = load_head(&Path::new(&skins).join(format!("{}.png", entry.name)));
+				head.ok().map(|head| (entry.name.clone(), head))
+			})
+			.collect::<HashMap<_, _>>()
+	});
+
+	let image = render_tab_list(
+		&tab_list,
+		heads.as_ref(),
+		&load_fonts(),
+		&load_background(),
+		2,
+		&palette,
+	);
+
+	prompt_save(&image);
+
+	main()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_entries() {
+		let tab_list = parse_tab_list(
+			"header: &6Server\nheader: line two\nSteve|42\n&c[A] |Alex| &7afk|180\nNotch",
+		)
+		.unwrap();
+		assert_eq!(tab_list.header, "&6Server\\nline two");
+		assert_eq!(tab_list.entries.len(), 3);
+		assert_eq!(
+			tab_list.entries[1],
+			TabEntry {
+				prefix: "&c[A] ".to_string(),
+				name:   "Alex".to_string(),
+				suffix: " &7afk".to_string(),
+				ping:   180,
+			}
+		);
+		assert_eq!(tab_list.entries[2].ping, 0);
+		assert!(parse_tab_list("Steve|fast").is_err());
+		assert!(parse_tab_list("a|b|c").is_err());
+	}
+
+	#[test]
+	fn balances_columns() {
+		assert_eq!(columns(5), (5, 1));
+		assert_eq!(columns(20), (20, 1));
+		assert_eq!(columns(21), (11, 2));
+		assert_eq!(columns(41), (14, 3));
+		assert_eq!(columns(80), (20, 4));
+	}
+
+	#[test]
+	fn maps_ping_to_bars() {
+		let bars = |ping| {
+			TabEntry {
+				ping,
+				..Default::default()
+			}
+			.ping_bars()
+		};
+		assert_eq!(bars(-1), None);
+		assert_eq!(bars(149), Some(5));
+		assert_eq!(bars(150), Some(4));
+		assert_eq!(bars(999), Some(2));
+		assert_eq!(bars(1000), Some(1));
+	}
+
+	#[test]
+	fn draws_entry_backgrounds() {
+		// a nameless entry is only its slot and ping icon, centered on the 100 pixel wide screen
+		let tab_list = parse_tab_list("|42").unwrap();
+		let background = RgbImage::from_pixel(200, 100, Rgb([100, 100, 100]));
+		let image = render_tab_list(
+			&tab_list,
+			None,
+			&load_fonts(),
+			&background,
+			1,
+			&Palette::java(),
+		);
+
+		// the list's backdrop darkens, and the slot is tinted white over it
+		let (left, top) = (50 - PING_WIDTH / 2, 10);
+		assert_eq!(image.get_pixel(left as u32, top - 1), &Rgb([50, 50, 50]));
+		assert!(image.get_pixel(left as u32, top)[0] > 50);
+		assert_eq!(image.get_pixel(0, 0), &Rgb([100, 100, 100]));
+	}
+
+	#[test]
+	fn fits_a_background_narrower_than_the_border() {
+		// the 20 pixel wide screen leaves no room for slots, so only the backdrop's edges remain
+		let tab_list = parse_tab_list("Steve|42").unwrap();
+		let background = RgbImage::from_pixel(40, 40, Rgb([100, 100, 100]));
+		let image = render_tab_list(
+			&tab_list,
+			None,
+			&load_fonts(),
+			&background,
+			1,
+			&Palette::java(),
+		);
+		assert_eq!(image.get_pixel(10, 9), &Rgb([50, 50, 50]));
+	}
+}
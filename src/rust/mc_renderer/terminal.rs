@@ -0,0 +1,132 @@
+use std::fmt::Write;
+
+use image::imageops::{self, FilterType};
+use image::{Rgb, RgbImage};
+
+/// Part of a render that holds the text within `bounds`, as `Renderer::text_bounds` returns
+/// them, with a `margin` around it so the preview doesn't waste the terminal on empty background.
+pub fn crop_to_text(
+	image: &RgbImage,
+	(left, top, right, bottom): (f32, f32, f32, f32),
+	margin: f32,
+) -> RgbImage {
+	let left = ((left - margin).max(0.0) as u32).min(image.width().saturating_sub(1));
+	let top = ((top - margin).max(0.0) as u32).min(image.height().saturating_sub(1));
+	let right = ((right + margin).max(0.0) as u32).min(image.width());
+	let bottom = ((bottom + margin).max(0.0) as u32).min(image.height());
+
+	imageops::crop_imm(
+		image,
+		left,
+		top,
+		right.saturating_sub(left).max(1),
+		bottom.saturating_sub(top).max(1),
+	)
+	.to_image()
+}
+
+/// Scales `image` down to fit in `columns` by `rows` cells, two pixels per cell, keeping its
+/// aspect ratio. Images that already fit are left at their size.
+pub fn fit(
+	image: &RgbImage,
+	columns: u32,
+	rows: u32,
+) -> RgbImage {
+	let (max_width, max_height) = (columns.max(1), rows.max(1) * 2);
+	if image.width() <= max_width && image.height() <= max_height {
+		return image.clone();
+	}
+
+	let ratio = f32::min(
+		max_width as f32 / image.width() as f32,
+		max_height as f32 / image.height() as f32,
+	);
+	imageops::resize(
+		image,
+		((image.width() as f32 * ratio) as u32).max(1),
+		((image.height() as f32 * ratio) as u32).max(1),
+		FilterType::Triangle,
+	)
+}
+
+/// Draws `image` as rows of `▀` characters, the foreground color being the top pixel of the
+/// cell and the background color the one below it. Rows end with `\r\n` so they line up in raw
+/// mode too.
+pub fn half_blocks(image: &RgbImage) -> String {
+	let mut output = String::new();
+
+	for y in (0..image.height()).step_by(2) {
+		for x in 0..image.width() {
+			let Rgb([r, g, b]) = *image.get_pixel(x, y);
+			write!(output, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+			if y + 1 < image.height() {
+				let Rgb([r, g, b]) = *image.get_pixel(x, y + 1);
+				write!(output, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+			} else {
+				output.push_str("\x1b[49m");
+			}
+			output.push('▀');
+		}
+		output.push_str("\x1b[0m\r\n");
+	}
+
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mc_renderer::align::Align;
+	use crate::mc_renderer::renderer::Renderer;
+
+	#[test]
+	fn pairs_rows_into_cells() {
+		let mut image = RgbImage::from_pixel(2, 3, Rgb([0, 0, 0]));
+		image.put_pixel(0, 0, Rgb([255, 0, 0]));
+		image.put_pixel(0, 1, Rgb([0, 0, 255]));
+
+		let output = half_blocks(&image);
+		let rows: Vec<&str> = output.split_terminator("\r\n").collect();
+		assert_eq!(rows.len(), 2);
+		assert_eq!(rows[0].matches('▀').count(), 2);
+		assert!(rows[0].starts_with("\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀"));
+		// an odd last row has nothing below it
+		assert!(rows[1].contains("\x1b[49m"));
+	}
+
+	#[test]
+	fn fits_inside_terminal() {
+		let image = RgbImage::new(400, 100);
+		let fitted = fit(&image, 80, 24);
+		assert_eq!((fitted.width(), fitted.height()), (80, 20));
+		assert_eq!(fit(&RgbImage::new(10, 4), 80, 24).dimensions(), (10, 4));
+	}
+
+	#[test]
+	fn crops_around_text() {
+		let renderer = Renderer::new()
+			.with_margins(10.0, 36.0)
+			.with_background(Some(RgbImage::new(854, 480)));
+		let image = RgbImage::new(854, 480);
+		let crop = |renderer: &Renderer, text: &str| {
+			let (left, _, right, _) = renderer.text_bounds(text);
+			let cropped = crop_to_text(&image, renderer.text_bounds(text), 8.0);
+			(left, right, cropped.dimensions())
+		};
+
+		let (left, _, (width, height)) = crop(&renderer, "Hi");
+		assert_eq!(left, 10.0);
+		assert!(width < 60);
+		assert!(height <= 32);
+
+		// right aligned text ends at the right margin, so the crop does too
+		let right_aligned = renderer.clone().with_align(Align::Right);
+		let (left, right, (width, _)) = crop(&right_aligned, "Hi");
+		assert!(left > 800.0);
+		assert!(right <= 844.0);
+		assert!(width < 60);
+
+		let (left, _, _) = crop(&renderer, "{align:right}Hi\\n{align:left}Hi");
+		assert_eq!(left, 10.0);
+	}
+}
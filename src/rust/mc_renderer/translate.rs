@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fs;
+
+/// Resolves `{name}` placeholders and `{translate:key|arg|...}` components into plain formatted
+/// text before it is rendered. Translations use vanilla's language file format, where `%s` takes
+/// the next argument and `%1$s` a specific one.
+#[derive(Default)]
+pub struct Translator {
+	language:  HashMap<String, String>,
+	variables: HashMap<String, String>,
+}
+
+impl Translator {
+	pub fn new(
+		language: HashMap<String, String>,
+		variables: HashMap<String, String>,
+	) -> Self {
+		Translator {
+			language,
+			variables,
+		}
+	}
+
+	/// Loads a language file such as vanilla's `en_us.json`.
+	pub fn load_language(path: &str) -> Result<HashMap<String, String>, String> {
+		let json =
+			fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+		serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", path, e))
+	}
+
+	/// Parses `name=value` pairs separated by commas, e.g. `player=Steve, rank=&cAdmin`.
+	pub fn parse_variables(input: &str) -> HashMap<String, String> {
+		input
+			.split(',')
+			.filter_map(|pair| pair.split_once('='))
+			.map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+			.collect()
+	}
+
+	pub fn resolve(
+		&self,
+		text: &str,
+	) -> String {
+		self.resolve_after("", text)
+	}
+
+	/// Resolves `text` as if it followed `context`, so inserted values can restore its formatting.
+	fn resolve_after(
+		&self,
+		context: &str,
+		text: &str,
+	) -> String {
+		let chars: Vec<char> = text.chars().collect();
+		let mut resolved = String::new();
+		let mut i = 0;
+
+		while i < chars.len() {
+			match chars[i] {
+				| '\\' => {
+					// keep escapes intact, `\{` included so it can't start a head or alignment
+					// placeholder later on either
+					resolved.extend(chars.get(i..i + 2).unwrap_or(&chars[i..]));
+					i += 2;
+				},
+				| '{' => match closing_brace(&chars, i) {
+					| Some(close) => {
+						let inner: String = chars[i + 1..close].iter().collect();
+						let context = format!("{}{}", context, resolved);
+						let value = self.component(&context, &inner);
+						insert(&mut resolved, &context, &value);
+						i = close + 1;
+					},
+					| None => {
+						resolved.push('{');
+						i += 1;
+					},
+				},
+				| c => {
+					resolved.push(c);
+					i += 1;
+				},
+			}
+		}
+
+		resolved
+	}
+
+	fn component(
+		&self,
+		context: &str,
+		inner: &str,
+	) -> String {
+		let parts = split_arguments(inner);
+		let (name, args) = parts.split_first().unwrap();
+
+		if let Some(key) = name.strip_prefix("translate:") {
+			let args: Vec<String> = args
+				.iter()
+				.map(|arg| self.resolve_after(context, arg))
+				.collect();
+			return self.translate(context, key, &args);
+		}
+
+		match self.variables.get(name) {
+			| Some(value) if args.is_empty() => value.clone(),
+			| _ => format!("{{{}}}", inner),
+		}
+	}
+
+	/// Fills in a translation, or returns the raw key when the language has no entry for it.
+	fn translate(
+		&self,
+		context: &str,
+		key: &str,
+		args: &[String],
+	) -> String {
+		let Some(format) = self.language.get(key) else {
+			return key.to_string();
+		};
+
+		let chars: Vec<char> = format.chars().collect();
+		let mut translated = String::new();
+		let mut next_argument = 0;
+		let mut i = 0;
+
+		while i < chars.len() {
+			if chars[i] != '%' {
+				translated.push(chars[i]);
+				i += 1;
+				continue;
+			}
+
+			let digits = chars[i + 1..]
+				.iter()
+				.take_while(|c| c.is_ascii_digit())
+				.count();
+			let positional = digits > 0
+				&& chars.get(i + 1 + digits) == Some(&'$')
+				&& chars.get(i + 2 + digits) == Some(&'s');
+
+			let (argument, length) = if chars.get(i + 1) == Some(&'%') {
+				translated.push('%');
+				i += 2;
+				continue;
+			} else if chars.get(i + 1) == Some(&'s') {
+				next_argument += 1;
+				(args.get(next_argument - 1), 2)
+			} else if positional {
+				let index: usize = chars[i + 1..i + 1 + digits]
+					.iter()
+					.collect::<String>()
+					.parse()
+					.unwrap_or(0);
+				(
+					index.checked_sub(1).and_then(|index| args.get(index)),
+					digits + 3,
+				)
+			} else {
+				(None, 1)
+			};
+
+			match argument {
+				| Some(argument) => {
+					let context = format!("{}{}", context, translated);
+					insert(&mut translated, &context, argument);
+				},
+				| None => translated.extend(&chars[i..i + length]),
+			}
+			i += length;
+		}
+
+		translated
+	}
+}
+
+/// Index of the `}` matching the `{` at `open`, skipping escaped and nested braces.
+fn closing_brace(
+	chars: &[char],
+	open: usize,
+) -> Option<usize> {
+	let mut depth = 0;
+	let mut i = open;
+
+	while i < chars.len() {
+		match chars[i] {
+			| '\\' => i += 1,
+			| '{' => depth += 1,
+			| '}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(i);
+				}
+			},
+			| _ => {},
+		}
+		i += 1;
+	}
+
+	None
+}
+
+/// Splits a component on the `|` characters that are not inside a nested component.
+fn split_arguments(inner: &str) -> Vec<String> {
+	let mut parts = vec![String::new()];
+	let mut depth = 0;
+	let mut chars = inner.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			| '|' if depth == 0 => {
+				parts.push(String::new());
+				continue;
+			},
+			| '\\' => {
+				parts.last_mut().unwrap().push(c);
+				if let Some(escaped) = chars.next() {
+					parts.last_mut().unwrap().push(escaped);
+				}
+				continue;
+			},
+			| '{' => depth += 1,
+			| '}' => depth -= 1,
+			| _ => {},
+		}
+		parts.last_mut().unwrap().push(c);
+	}
+
+	parts
+}
+
+/// The format codes still in effect at the end of `text`, as they would have to be re-typed.
+fn active_codes(text: &str) -> String {
+	let mut codes = String::new();
+	let mut chars = text.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			| '\\' => {
+				chars.next();
+			},
+			| '&' | '§' => match chars.next() {
+				| Some('r') => codes.clear(),
+				| Some(code) if code.is_ascii_alphanumeric() => {
+					codes.push('&');
+					codes.push(code);
+				},
+				| _ => {},
+			},
+			| _ => {},
+		}
+	}
+
+	codes
+}
+
+/// Appends `value`, then restores the formatting of `context` if `value` changed it, so the
+/// codes inside a variable or argument don't leak into the rest of the message.
+fn insert(
+	resolved: &mut String,
+	context: &str,
+	value: &str,
+) {
+	resolved.push_str(value);
+
+	let before = active_codes(context);
+	if active_codes(&format!("{}{}", context, value)) != before {
+		resolved.push_str("&r");
+		resolved.push_str(&before);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn translator() -> Translator {
+		let language = HashMap::from([
+			("chat.type.text".to_string(), "<%s> %s".to_string()),
+			(
+				"commands.give.success".to_string(),
+				"Gave %2$s %1$s (100%%)".to_string(),
+			),
+		]);
+		let variables = Translator::parse_variables("player=Steve, rank=&cAdmin");
+		Translator::new(language, variables)
+	}
+
+	#[test]
+	fn substitutes_variables() {
+		let translator = translator();
+		assert_eq!(translator.resolve("Hi {player}!"), "Hi Steve!");
+		assert_eq!(
+			translator.resolve("{unknown} \\{player}"),
+			"{unknown} \\{player}"
+		);
+	}
+
+	#[test]
+	fn translates_with_arguments() {
+		let translator = translator();
+		assert_eq!(
+			translator.resolve("{translate:chat.type.text|{player}|hello}"),
+			"<Steve> hello"
+		);
+		assert_eq!(
+			translator.resolve("{translate:commands.give.success|Stone|{player}}"),
+			"Gave Steve Stone (100%)"
+		);
+	}
+
+	#[test]
+	fn missing_keys_and_arguments_stay_visible() {
+		let translator = translator();
+		assert_eq!(
+			translator.resolve("{translate:item.unknown}"),
+			"item.unknown"
+		);
+		assert_eq!(
+			translator.resolve("{translate:chat.type.text|Alex}"),
+			"<Alex> %s"
+		);
+	}
+
+	#[test]
+	fn inserted_codes_do_not_leak() {
+		let translator = translator();
+		assert_eq!(
+			translator.resolve("&e&l[{rank}] {player}"),
+			"&e&l[&cAdmin&r&e&l] Steve"
+		);
+		assert_eq!(
+			translator.resolve("&7{translate:chat.type.text|&a{player}|hi}"),
+			"&7<&aSteve&r&7> hi"
+		);
+	}
+
+	#[test]
+	fn keeps_renderer_escapes() {
+		let translator = translator();
+		assert_eq!(translator.resolve("\\&a \\\\{player}"), "\\&a \\\\Steve");
+	}
+}
@@ -1,4 +1,8 @@
 use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use arboard::Clipboard;
 
 pub fn input(
 	prompt: &str,
@@ -41,3 +45,42 @@ pub fn menu(options: Vec<&str>) -> u8 {
 
 	choice
 }
+
+/// Copies `text` to the clipboard, retrying for a moment while another program holds it, and
+/// says whether that worked.
+pub fn copy_text_to_clipboard(text: &str) {
+	for _ in 0..5 {
+		if let Ok(mut clipboard) = Clipboard::new() {
+			if clipboard.set_text(text).is_ok() {
+				println!("\n\x1b[32mSuccess!\x1b[0m Text copied to clipboard");
+				return;
+			}
+		}
+		thread::sleep(Duration::from_millis(100));
+	}
+	println!("\n\x1b[31mFailed to copy the text to the clipboard\x1b[0m");
+}
+
+/// A path in the temp directory that is unique to the test `name` in this process, removed with
+/// everything in it once dropped.
+#[cfg(test)]
+pub struct TempPath(pub std::path::PathBuf);
+
+#[cfg(test)]
+impl TempPath {
+	pub fn new(name: &str) -> Self {
+		TempPath(std::env::temp_dir().join(format!(
+			"random-things-{}-{}",
+			std::process::id(),
+			name
+		)))
+	}
+}
+
+#[cfg(test)]
+impl Drop for TempPath {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+		let _ = std::fs::remove_file(&self.0);
+	}
+}
@@ -1,140 +1,586 @@
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
+use std::time::Duration;
+
+use rand::seq::IndexedRandom;
 
 use crate::main;
-use crate::stuff::input;
+use crate::stuff::{copy_text_to_clipboard, input, menu};
+
+mod daily;
+mod feedback;
+mod hard_mode;
+mod share;
+mod solver;
+mod stats;
+
+use daily::Puzzle;
+use feedback::{Format, LetterResult, render, score};
+use share::{Squares, share_text};
+use stats::StatsFile;
+
+/// Words that can be the answer, one per line.
+const ANSWERS: &str = include_str!("assets/wordle_answers.txt");
+/// Every word accepted as a guess, the answers included.
+const ALLOWED: &str = include_str!("assets/wordle_allowed.txt");
+
+/// Guesses a round allows unless the player picks another number.
+const MAX_ATTEMPTS: u16 = 6;
+
+const API_TIMEOUT: Duration = Duration::from_secs(5);
+
+const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+fn answers(length: u8) -> Vec<&'static str> {
+	ANSWERS
+		.lines()
+		.filter(|word| word.len() == length as usize)
+		.collect()
+}
+
+fn allowed(length: u8) -> Vec<&'static str> {
+	ALLOWED
+		.lines()
+		.filter(|word| word.len() == length as usize)
+		.collect()
+}
 
-async fn get_word(length: u8) -> String {
+fn is_allowed(word: &str) -> bool { ALLOWED.lines().any(|allowed| allowed == word) }
+
+/// Guesses are compared in lowercase, like the word lists.
+fn normalize(guess: &str) -> String { guess.trim().to_lowercase() }
+
+async fn fetch_word(length: u8) -> Result<String, String> {
 	let url = format!(
 		"https://random-word-api.herokuapp.com/word?number=1&length={}",
 		length
 	);
-	let response = reqwest::get(&url).await.unwrap();
-	let word: Vec<String> = response.json().await.unwrap();
-	word[0].clone()
+	let client = reqwest::Client::builder()
+		.timeout(API_TIMEOUT)
+		.build()
+		.map_err(|e| e.to_string())?;
+	let response = client.get(&url).send().await.map_err(|e| e.to_string())?;
+	let words: Vec<String> = response.json().await.map_err(|e| e.to_string())?;
+
+	words
+		.into_iter()
+		.next()
+		.map(|word| word.to_lowercase())
+		.filter(|word| {
+			word.len() == length as usize && word.chars().all(|c| c.is_ascii_lowercase())
+		})
+		.ok_or_else(|| "the API didn't return a word".to_string())
+}
+
+/// Picks a word from the bundled list, or from the web API when `online` is set, falling back to
+/// the bundled list if it fails or takes too long.
+async fn get_word(
+	length: u8,
+	online: bool,
+) -> String {
+	if online {
+		match fetch_word(length).await {
+			| Ok(word) => return word,
+			| Err(e) => println!(
+				"\x1b[33mFailed to get a word online ({}), using the bundled list\x1b[0m",
+				e
+			),
+		}
+	}
+
+	answers(length)
+		.choose(&mut rand::thread_rng())
+		.expect("No bundled words of that length")
+		.to_string()
 }
 
+/// Checks and scores a guess, counting it if it is a word of the right length. In hard mode
+/// `hints` are the rows guessed so far, whose hints the guess has to use.
 fn process_guess(
 	word: &str,
 	guess: &str,
+	hints: Option<&[Row]>,
 	guess_count: &mut u16,
-) -> String {
-	let mut remaining_letters: Vec<Option<char>> = word.chars().map(Some).collect();
-	let mut correct = vec![None; word.len()];
+) -> Result<Vec<LetterResult>, String> {
+	let guess = &normalize(guess);
 
-	if guess.len() != word.len() {
-		return format!(
+	if guess.chars().count() != word.chars().count() {
+		return Err(format!(
 			"\x1b[31mGuess must be \x1b[34m{}\x1b[31m letters long.\x1b[0m",
-			word.len()
-		);
+			word.chars().count()
+		));
+	}
+
+	// words from the web API aren't always in the bundled list
+	if guess != word && !is_allowed(guess) {
+		return Err(format!(
+			"\x1b[34m{}\x1b[31m is not in the word list.\x1b[0m",
+			guess
+		));
+	}
+
+	if let Some(rows) = hints {
+		hard_mode::check(rows, guess)?;
 	}
 
 	*guess_count += 1;
+	Ok(score(word, guess))
+}
 
-	// First pass: mark correct positions
-	for (i, guess_char) in guess.chars().enumerate() {
-		if guess_char == word.chars().nth(i).unwrap() {
-			correct[i] = Some(format!("\x1b[32m{}\x1b[0m", guess_char));
-			remaining_letters[i] = None;
-		}
+/// A guess with its result.
+type Row = (String, Vec<LetterResult>);
+
+/// The rows `guesses` leave when the word is `word`, for tests.
+#[cfg(test)]
+fn rows(
+	word: &str,
+	guesses: &[&str],
+) -> Vec<Row> {
+	guesses
+		.iter()
+		.map(|guess| (guess.to_string(), score(word, guess)))
+		.collect()
+}
+
+/// Whether the last row guessed the word.
+fn solved(rows: &[Row]) -> bool {
+	rows.last().is_some_and(|(_, results)| {
+		results
+			.iter()
+			.all(|result| *result == LetterResult::Correct)
+	})
+}
+
+/// Guessed rows in order, then an empty row for each attempt left.
+fn board(
+	rows: &[Row],
+	max_attempts: u16,
+	length: usize,
+	format: Format,
+) -> String {
+	let mut lines: Vec<String> = rows
+		.iter()
+		.map(|(guess, results)| render(guess, results, format))
+		.collect();
+	let empty = match format {
+		| Format::Ansi => format!("\x1b[90m{}\x1b[0m", "_".repeat(length)),
+		| _ => " _ ".repeat(length),
+	};
+	while lines.len() < max_attempts as usize {
+		lines.push(empty.clone());
 	}
+	lines.join("\n")
+}
 
-	// Second pass: mark present but incorrect positions
-	for (i, guess_char) in guess.chars().enumerate() {
-		if correct[i].is_none() {
-			if let Some(pos) = remaining_letters
-				.iter()
-				.position(|&c| c == Some(guess_char))
-			{
-				correct[i] = Some(format!("\x1b[33m{}\x1b[0m", guess_char));
-				remaining_letters[pos] = None;
-			} else {
-				correct[i] = Some(format!("\x1b[90m{}\x1b[0m", guess_char));
-			}
+/// The best result of every letter guessed so far.
+fn key_states(rows: &[Row]) -> HashMap<char, LetterResult> {
+	let mut states = HashMap::new();
+	for (guess, results) in rows {
+		for (c, result) in guess.chars().zip(results) {
+			let best = states.entry(c).or_insert(*result);
+			*best = (*result).max(*best);
 		}
 	}
+	states
+}
 
-	correct
+/// A QWERTY keyboard with each key shown like a guessed letter with its best result.
+fn keyboard(
+	states: &HashMap<char, LetterResult>,
+	format: Format,
+) -> String {
+	KEYBOARD_ROWS
 		.iter()
-		.map(|c| c.as_ref().unwrap().as_str())
-		.collect::<String>()
+		.enumerate()
+		.map(|(i, row)| {
+			let keys: Vec<String> = row
+				.chars()
+				.map(|c| {
+					let key = c.to_ascii_uppercase().to_string();
+					match (states.get(&c), format) {
+						// without colors, ruled out letters are blanked out to tell them apart
+						| (Some(LetterResult::Absent), Format::Plain) => " - ".to_string(),
+						| (Some(result), _) => render(&key, &[*result], format),
+						| (None, Format::Plain) => format!(" {} ", key),
+						// unused letters keep the terminal's own color
+						| (None, _) => format!("\x1b[0m{}\x1b[0m", key),
+					}
+				})
+				.collect();
+			// each row starts a little further right, like on a real keyboard, and plain keys
+			// are already spaced out by their brackets
+			let separator = if format == Format::Plain { "" } else { " " };
+			format!("{}{}", " ".repeat(i), keys.join(separator))
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
 }
 
-pub async fn start() {
-	let word: String = get_word(5).await;
+/// Plays one round, returning the rows it was guessed in. `title` is shown above the board.
+fn play(
+	word: &str,
+	title: &str,
+	max_attempts: u16,
+	format: Format,
+	hard_mode: bool,
+) -> Vec<Row> {
 	let mut guess_count: u16 = 0;
+	let mut rows: Vec<Row> = Vec::new();
+	let mut message = String::new();
 
-	print!("\nEnter your guess:");
-	loop {
-		let guess = input("", true);
-
-		print!("\x1b[1A\x1b[2K");
+	let redraw = |rows: &[Row], message: &str| {
+		print!("\x1b[2J\x1b[H");
+		println!("{}\n", title);
+		println!(
+			"{}",
+			board(rows, max_attempts, word.chars().count(), format)
+		);
+		println!("\n{}", keyboard(&key_states(rows), format));
+		if !message.is_empty() {
+			println!("\n{}", message);
+		}
 		io::stdout().flush().unwrap();
+	};
 
-		let result = process_guess(&word, &guess, &mut guess_count);
-		print!("{}", result);
+	while guess_count < max_attempts {
+		redraw(&rows, &message);
+		let guess = normalize(&input(
+			"\nEnter your guess, or /hint for a suggestion:",
+			true,
+		));
+		if guess == "/hint" {
+			let (candidates, guess) = suggest(&rows, hard_mode);
+			message = hint(&candidates, guess);
+			continue;
+		}
+
+		let hints = hard_mode.then_some(rows.as_slice());
+		match process_guess(word, &guess, hints, &mut guess_count) {
+			| Ok(results) => {
+				rows.push((guess.clone(), results));
+				message.clear();
+			},
+			// rejected guesses don't take up a row
+			| Err(e) => {
+				message = e;
+				continue;
+			},
+		}
 
 		if guess == word {
+			redraw(&rows, "");
 			println!(
-				"\n\nCongratulations! You guessed the word in {} attempts",
+				"\nCongratulations! You guessed the word in {} attempts",
 				guess_count
 			);
-			break;
+			return rows;
+		}
+	}
+
+	redraw(&rows, "");
+	println!("\nOut of guesses! The word was \x1b[32m{}\x1b[0m", word);
+	rows
+}
+
+pub async fn start() {
+	println!("\nWhat do you want to play?");
+	let daily = match menu(vec![
+		"Free play, a new random word every round",
+		"The daily puzzle, the same word for everyone on the same day",
+		"Solve a puzzle from another Wordle client",
+	]) {
+		| 2 => true,
+		| 3 => {
+			loop {
+				solve();
+				if menu(vec!["Solve another", "Back to the menu"]) != 1 {
+					break;
+				}
+			}
+			return main();
+		},
+		| _ => false,
+	};
+
+	let online = !daily && {
+		println!("\nWhere should the word come from?");
+		menu(vec![
+			"The bundled word list",
+			"random-word-api.herokuapp.com, or the bundled list without a connection",
+		]) == 2
+	};
+
+	let max_attempts = input(
+		&format!(
+			"\nHow many guesses do you get? (leave empty for {})",
+			MAX_ATTEMPTS
+		),
+		true,
+	)
+	.parse()
+	.ok()
+	.filter(|attempts| *attempts > 0)
+	.unwrap_or(MAX_ATTEMPTS);
+
+	println!("\nHow should guesses be shown?");
+	let format = match menu(vec!["In color", "As plain text, [correct] and (present)"]) {
+		| 2 => Format::Plain,
+		| _ => Format::Ansi,
+	};
+
+	println!("\nWhich mode do you want to play?");
+	let hard_mode = menu(vec![
+		"Normal",
+		"Hard, every hint has to be used in the guesses after it",
+	]) == 2;
+
+	'rounds: loop {
+		let puzzle = daily.then(choose_puzzle).flatten();
+		let (word, title) = match puzzle {
+			| Some(puzzle) => (
+				puzzle.answer(&answers(5)).to_string(),
+				format!("Daily puzzle {} ({})", puzzle.number, puzzle.date),
+			),
+			| None => (get_word(5, online).await, "Free play".to_string()),
+		};
+		let rows = play(&word, &title, max_attempts, format, hard_mode);
+		record_stats(puzzle, &rows, max_attempts);
+
+		let share = |squares| {
+			share_text(
+				puzzle.map(|puzzle| puzzle.number),
+				&rows,
+				max_attempts,
+				hard_mode,
+				squares,
+			)
+		};
+		println!("\n{}\n", share(Squares::Standard));
+
+		loop {
+			match menu(vec![
+				"Play again",
+				"Copy the result",
+				"Copy the result with high contrast colors",
+				"Back to the menu",
+			]) {
+				| 1 => break,
+				| 2 => copy_text_to_clipboard(&share(Squares::Standard)),
+				| 3 => {
+					let text = share(Squares::HighContrast);
+					println!("\n{}", text);
+					copy_text_to_clipboard(&text);
+				},
+				| _ => break 'rounds,
+			}
+			println!();
 		}
 	}
 
 	main()
 }
 
+/// The words that still fit `rows`, and the solver's pick for the next guess. In hard mode only
+/// guesses that use every hint are considered.
+fn suggest(
+	rows: &[Row],
+	hard_mode: bool,
+) -> (Vec<&'static str>, Option<&'static str>) {
+	let candidates = solver::candidates(rows, &answers(5));
+	if rows.is_empty() {
+		return (candidates, Some(solver::OPENER));
+	}
+
+	let guesses: Vec<&str> = allowed(5)
+		.into_iter()
+		.filter(|guess| !hard_mode || hard_mode::check(rows, guess).is_ok())
+		.collect();
+	let guess = solver::best_guess(&candidates, &guesses);
+	(candidates, guess)
+}
+
+/// The `/hint` message for the `candidates` and `guess` that `suggest` picked.
+fn hint(
+	candidates: &[&str],
+	guess: Option<&str>,
+) -> String {
+	match guess {
+		| Some(guess) => format!(
+			"Try \x1b[34m{}\x1b[0m, {} possible {} left.",
+			guess,
+			candidates.len(),
+			if candidates.len() == 1 {
+				"word"
+			} else {
+				"words"
+			}
+		),
+		| None => "\x1b[31mNo word in the list fits the hints.\x1b[0m".to_string(),
+	}
+}
+
+/// Suggests guesses for a puzzle played in another Wordle client, from the colors it showed.
+fn solve() {
+	let mut rows: Vec<Row> = Vec::new();
+
+	loop {
+		let (candidates, suggestion) = suggest(&rows, false);
+		let Some(suggestion) = suggestion else {
+			println!("\n\x1b[31mNo word in the list fits those colors.\x1b[0m");
+			return;
+		};
+		if candidates.len() == 1 {
+			println!("\nThe word is \x1b[32m{}\x1b[0m", suggestion);
+			return;
+		}
+		println!("\n{}", hint(&candidates, Some(suggestion)));
+
+		let guess = loop {
+			let guess = normalize(&input(
+				&format!(
+					"\nEnter the word you guessed (leave empty for {}):",
+					suggestion
+				),
+				true,
+			));
+			match guess.as_str() {
+				| "" => break suggestion.to_string(),
+				| guess if guess.len() == 5 && is_allowed(guess) => break guess.to_string(),
+				| guess => println!(
+					"\x1b[34m{}\x1b[31m is not a 5 letter word in the list.\x1b[0m",
+					guess
+				),
+			}
+		};
+
+		let results = loop {
+			let colors = input(
+				"Enter the colors you got, g for green, y for yellow and . for gray:",
+				true,
+			);
+			match solver::parse_colors(&colors, 5) {
+				| Ok(results) => break results,
+				| Err(e) => println!("{}", e),
+			}
+		};
+
+		rows.push((guess, results));
+		if solved(&rows) {
+			println!("\nSolved in {} guesses!", rows.len());
+			return;
+		}
+	}
+}
+
+/// Saves the round to the stats file and shows the stats of its mode.
+fn record_stats(
+	puzzle: Option<Puzzle>,
+	rows: &[Row],
+	max_attempts: u16,
+) {
+	let path = StatsFile::path();
+	// a file that can't be read is left alone rather than replaced with this one round
+	let mut file = match StatsFile::load(&path) {
+		| Ok(file) => file,
+		| Err(e) => {
+			println!("\n\x1b[31m{}\x1b[0m", e);
+			return;
+		},
+	};
+
+	let guesses = solved(rows).then_some(rows.len() as u16);
+	let stats = match puzzle {
+		| Some(puzzle) => {
+			let today = Puzzle::today().map_or(0, |today| today.number);
+			if !file.record_daily(puzzle.number, today, guesses) {
+				println!("\nOnly the first round of today's puzzle counts towards the stats.");
+			}
+			&file.daily
+		},
+		| None => {
+			file.free_play.record(guesses);
+			&file.free_play
+		},
+	};
+	println!("\n{}", stats.chart(max_attempts, guesses));
+
+	if let Err(e) = file.save(&path) {
+		println!("\n\x1b[31m{}\x1b[0m", e);
+	}
+}
+
+/// Asks which daily puzzle to play until the answer is one, or plays a free round when there is
+/// no puzzle today.
+fn choose_puzzle() -> Option<Puzzle> {
+	if let Err(e) = Puzzle::today() {
+		println!("\n\x1b[31m{}\x1b[0m, so this round is free play.", e);
+		return None;
+	}
+	loop {
+		let options = input(
+			"\nReplay a past puzzle with --date YYYY-MM-DD or --seed <puzzle number>, or leave \
+			 empty for today's:",
+			true,
+		);
+		match Puzzle::parse(&options) {
+			| Ok(puzzle) => return Some(puzzle),
+			| Err(e) => println!("\x1b[31m{}\x1b[0m", e),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use tokio::runtime::Runtime;
 
 	use super::*;
+	use crate::wordle::feedback::LetterResult::*;
 
 	#[test]
 	fn returns_word_in_correct_order() {
 		let rt = Runtime::new().unwrap();
-		let word = rt.block_on(get_word(5));
+		let word = rt.block_on(get_word(5, false));
 		assert_eq!(word.len(), 5);
 	}
 
+	#[test]
+	fn bundled_answers_are_allowed_guesses() {
+		let allowed: Vec<&str> = ALLOWED.lines().collect();
+		for word in answers(5) {
+			assert!(word.chars().all(|c| c.is_ascii_lowercase()), "{}", word);
+			assert!(allowed.contains(&word), "{}", word);
+		}
+		assert!(answers(5).len() > 1000);
+	}
+
 	#[test]
 	fn correctly_colors_correct_positions() {
 		let word = "apple".to_string();
 		let guess = "apple".to_string();
 		let mut guess_count = 0;
-		let result = process_guess(&word, &guess, &mut guess_count);
-		assert_eq!(
-			result,
-			"\x1b[32ma\x1b[0m\x1b[32mp\x1b[0m\x1b[32mp\x1b[0m\x1b[32ml\x1b[0m\x1b[32me\x1b[0m"
-		);
+		let result = process_guess(&word, &guess, None, &mut guess_count);
+		assert_eq!(result, Ok(vec![Correct; 5]));
 		assert_eq!(guess_count, 1);
 	}
 
 	#[test]
 	fn correctly_colors_present_but_incorrect_positions() {
 		let word = "apple".to_string();
-		let guess = "pleap".to_string();
+		let guess = "paled".to_string();
 		let mut guess_count = 0;
-		let result = process_guess(&word, &guess, &mut guess_count);
-		assert_eq!(
-			result,
-			"\x1b[33mp\x1b[0m\x1b[33ml\x1b[0m\x1b[33me\x1b[0m\x1b[33ma\x1b[0m\x1b[33mp\x1b[0m"
-		);
+		let result = process_guess(&word, &guess, None, &mut guess_count);
+		assert_eq!(result, Ok(vec![Present, Present, Present, Present, Absent]));
 		assert_eq!(guess_count, 1);
 	}
 
 	#[test]
 	fn correctly_colors_incorrect_positions() {
 		let word = "apple".to_string();
-		let guess = "zzzzz".to_string();
+		let guess = "dirty".to_string();
 		let mut guess_count = 0;
-		let result = process_guess(&word, &guess, &mut guess_count);
-		assert_eq!(
-			result,
-			"\x1b[90mz\x1b[0m\x1b[90mz\x1b[0m\x1b[90mz\x1b[0m\x1b[90mz\x1b[0m\x1b[90mz\x1b[0m"
-		);
+		let result = process_guess(&word, &guess, None, &mut guess_count);
+		assert_eq!(result, Ok(vec![Absent; 5]));
 		assert_eq!(guess_count, 1);
 	}
 
@@ -143,10 +589,10 @@ mod tests {
 		let word = "apple".to_string();
 		let guess = "app".to_string();
 		let mut guess_count = 0;
-		let result = process_guess(&word, &guess, &mut guess_count);
+		let result = process_guess(&word, &guess, None, &mut guess_count);
 		assert_eq!(
 			result,
-			"\x1b[31mGuess must be \x1b[34m5\x1b[31m letters long.\x1b[0m"
+			Err("\x1b[31mGuess must be \x1b[34m5\x1b[31m letters long.\x1b[0m".to_string())
 		);
 		assert_eq!(guess_count, 0);
 	}
@@ -157,19 +603,117 @@ mod tests {
 		let mut guess_count = 0;
 
 		let guess = "app".to_string();
-		let _ = process_guess(&word, &guess, &mut guess_count);
+		let _ = process_guess(&word, &guess, None, &mut guess_count);
 		assert_eq!(guess_count, 0);
 
 		let guess = "apples".to_string();
-		let _ = process_guess(&word, &guess, &mut guess_count);
+		let _ = process_guess(&word, &guess, None, &mut guess_count);
 		assert_eq!(guess_count, 0);
 
-		let guess = "pleap".to_string();
-		let _ = process_guess(&word, &guess, &mut guess_count);
+		let guess = "paled".to_string();
+		let _ = process_guess(&word, &guess, None, &mut guess_count);
 		assert_eq!(guess_count, 1);
 
 		let guess = "zzzzz".to_string();
-		let _ = process_guess(&word, &guess, &mut guess_count);
+		let _ = process_guess(&word, &guess, None, &mut guess_count);
+		assert_eq!(guess_count, 1);
+
+		let guess = "dirty".to_string();
+		let _ = process_guess(&word, &guess, None, &mut guess_count);
+		assert_eq!(guess_count, 2);
+	}
+
+	#[test]
+	fn board_keeps_every_guess() {
+		let rows = rows("apple", &["paled", "dirty"]);
+		let board = board(&rows, 4, 5, Format::Ansi);
+		let lines: Vec<&str> = board.lines().collect();
+		assert_eq!(lines.len(), 4);
+		assert_eq!(lines[0], render("paled", &rows[0].1, Format::Ansi));
+		assert_eq!(lines[1], render("dirty", &rows[1].1, Format::Ansi));
+		assert_eq!(lines[3], "\x1b[90m_____\x1b[0m");
+	}
+
+	#[test]
+	fn keys_keep_their_best_state() {
+		let rows = rows("apple", &["paled", "apple"]);
+
+		let states = key_states(&rows[..1]);
+		assert_eq!(states[&'p'], Present);
+		assert_eq!(states[&'d'], Absent);
+		assert_eq!(states.get(&'z'), None);
+
+		let states = key_states(&rows);
+		assert_eq!(states[&'p'], Correct);
+		assert_eq!(states[&'d'], Absent);
+
+		let keyboard = keyboard(&states, Format::Ansi);
+		assert_eq!(keyboard.lines().count(), 3);
+		assert!(keyboard.contains("\x1b[32mP\x1b[0m"));
+		assert!(keyboard.contains("\x1b[90mD\x1b[0m"));
+		assert!(keyboard.contains("\x1b[0mQ\x1b[0m"));
+
+		let plain = super::keyboard(&key_states(&rows[..1]), Format::Plain);
+		assert!(plain.starts_with(" Q  W (E) R  T  Y  U  I  O (P)"));
+		assert!(plain.contains(" S  -  F "));
+	}
+
+	#[test]
+	fn rejects_words_not_in_the_list() {
+		let word = "apple".to_string();
+		let mut guess_count = 0;
+		let result = process_guess(&word, "zzzzz", None, &mut guess_count);
+		assert_eq!(
+			result,
+			Err("\x1b[34mzzzzz\x1b[31m is not in the word list.\x1b[0m".to_string())
+		);
+		assert_eq!(guess_count, 0);
+
+		// an answer from the web API is always accepted
+		let _ = process_guess("qzxjv", "qzxjv", None, &mut guess_count);
+		assert_eq!(guess_count, 1);
+	}
+
+	#[test]
+	fn hard_mode_rejects_unused_hints() {
+		let rows = rows("apple", &["paled"]);
+		let mut guess_count = 1;
+		let result = process_guess("apple", "dirty", Some(&rows), &mut guess_count);
+		assert_eq!(
+			result,
+			Err("\x1b[31mGuess must contain \x1b[34mP\x1b[31m.\x1b[0m".to_string())
+		);
+		assert_eq!(guess_count, 1);
+
+		assert!(process_guess("apple", "dirty", None, &mut guess_count).is_ok());
+		assert_eq!(guess_count, 2);
+	}
+
+	#[test]
+	fn ignores_case() {
+		let word = "apple".to_string();
+		let mut guess_count = 0;
+		assert_eq!(
+			process_guess(&word, "APPLE", None, &mut guess_count),
+			process_guess(&word, "apple", None, &mut guess_count)
+		);
 		assert_eq!(guess_count, 2);
 	}
+
+	#[test]
+	fn hints_fit_the_hints_so_far() {
+		assert_eq!(suggest(&[], false).1, Some(solver::OPENER));
+
+		let rows = rows("apple", &["raise"]);
+		let (candidates, guess) = suggest(&rows, true);
+		assert!(candidates.contains(&"apple"));
+		assert!(hard_mode::check(&rows, guess.unwrap()).is_ok());
+
+		let rows = self::rows("apple", &["raise", "table", "maple"]);
+		let (candidates, guess) = suggest(&rows, false);
+		assert_eq!(
+			hint(&candidates, guess),
+			"Try \x1b[34mapple\x1b[0m, 1 possible word left."
+		);
+	}
 }
@@ -0,0 +1,184 @@
+use chrono::{Days, Local, NaiveDate};
+
+/// The day of puzzle 1. Changing it changes every puzzle, so it stays fixed.
+const EPOCH: (i32, u32, u32) = (2025, 1, 1);
+
+/// Shuffles the answers the same way everywhere, so the order can't be guessed from the
+/// alphabetical list.
+const SHUFFLE_SEED: u64 = 0x5752_444c_4521;
+
+/// A daily puzzle, numbered from 1 on the epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+	pub number: u32,
+	pub date:   NaiveDate,
+}
+
+fn epoch() -> NaiveDate {
+	let (year, month, day) = EPOCH;
+	NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+impl Puzzle {
+	/// Today's puzzle, which fails when the clock is set before the first one.
+	pub fn today() -> Result<Self, String> {
+		let today = Local::now().date_naive();
+		Puzzle::for_date(today, today)
+	}
+
+	/// The puzzle of `date`, which can't be after `today` so future answers stay hidden.
+	pub fn for_date(
+		date: NaiveDate,
+		today: NaiveDate,
+	) -> Result<Self, String> {
+		let days = (date - epoch()).num_days();
+		if days < 0 {
+			return Err(format!("The first puzzle is from {}", epoch()));
+		}
+		if date > today {
+			return Err(format!("The puzzle for {} isn't out yet", date));
+		}
+		Ok(Puzzle {
+			number: days as u32 + 1,
+			date,
+		})
+	}
+
+	pub fn numbered(
+		number: u32,
+		today: NaiveDate,
+	) -> Result<Self, String> {
+		if number == 0 {
+			return Err("Puzzles are numbered from 1".to_string());
+		}
+		let date = epoch()
+			.checked_add_days(Days::new(number as u64 - 1))
+			.ok_or_else(|| format!("Puzzle {} isn't out yet", number))?;
+		Puzzle::for_date(date, today).map_err(|_| format!("Puzzle {} isn't out yet", number))
+	}
+
+	/// Parses `--date 2025-01-31` or `--seed 31` to replay a past puzzle, where the seed is the
+	/// puzzle number. No options give today's puzzle, and any other options are an error.
+	pub fn parse(options: &str) -> Result<Self, String> {
+		Puzzle::parse_on(options, Local::now().date_naive())
+	}
+
+	fn parse_on(
+		options: &str,
+		today: NaiveDate,
+	) -> Result<Self, String> {
+		let parts: Vec<&str> = options.split_whitespace().collect();
+		match parts.as_slice() {
+			| [] => Puzzle::for_date(today, today),
+			| ["--date", date] => {
+				let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+					.map_err(|_| format!("{} is not a YYYY-MM-DD date", date))?;
+				Puzzle::for_date(date, today)
+			},
+			| ["--seed", seed] => {
+				let number = seed
+					.parse()
+					.map_err(|_| format!("{} is not a puzzle number", seed))?;
+				Puzzle::numbered(number, today)
+			},
+			| _ => Err(format!("Unknown options: {}", options)),
+		}
+	}
+
+	/// The answer of this puzzle. Puzzles go through the shuffled answers in order, so no word
+	/// comes up twice until all of them have.
+	pub fn answer<'a>(
+		&self,
+		answers: &[&'a str],
+	) -> &'a str {
+		let order = shuffled(answers.len());
+		answers[order[(self.number as usize - 1) % answers.len()]]
+	}
+}
+
+/// splitmix64, which unlike `rand`'s generators is guaranteed to stay the same between versions.
+fn next(state: &mut u64) -> u64 {
+	*state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+	let mut z = *state;
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	z ^ (z >> 31)
+}
+
+/// The indices up to `len` in a fixed random order.
+fn shuffled(len: usize) -> Vec<usize> {
+	let mut order: Vec<usize> = (0..len).collect();
+	let mut state = SHUFFLE_SEED;
+	for i in (1..len).rev() {
+		let j = (next(&mut state) % (i as u64 + 1)) as usize;
+		order.swap(i, j);
+	}
+	order
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::wordle::answers;
+
+	#[test]
+	fn dates_and_numbers_match() {
+		let date = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+		let puzzle = Puzzle::for_date(date, date).unwrap();
+		assert_eq!(puzzle.number, 32);
+		assert_eq!(Puzzle::numbered(32, date), Ok(puzzle));
+		assert_eq!(Puzzle::parse("--date 2025-02-01"), Ok(puzzle));
+		assert_eq!(Puzzle::parse(" --seed 32 "), Ok(puzzle));
+
+		assert!(Puzzle::parse("--date 2024-12-31").is_err());
+		assert!(Puzzle::parse("--seed 0").is_err());
+		assert!(Puzzle::parse("--day 3").is_err());
+	}
+
+	#[test]
+	fn future_puzzles_stay_hidden() {
+		let today = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+		assert_eq!(Puzzle::parse_on("", today).unwrap().number, 32);
+		assert!(Puzzle::parse_on("--seed 32", today).is_ok());
+		assert_eq!(
+			Puzzle::parse_on("--seed 33", today),
+			Err("Puzzle 33 isn't out yet".to_string())
+		);
+		assert_eq!(
+			Puzzle::parse_on("--date 2025-02-02", today),
+			Err("The puzzle for 2025-02-02 isn't out yet".to_string())
+		);
+		assert!(Puzzle::parse_on(&format!("--seed {}", u32::MAX), today).is_err());
+
+		let tomorrow = Puzzle::today().unwrap().number + 1;
+		assert!(Puzzle::numbered(tomorrow, Local::now().date_naive()).is_err());
+
+		// a clock set before the first puzzle has no puzzle today, rather than panicking
+		let before = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+		assert!(Puzzle::parse_on("", before).is_err());
+	}
+
+	#[test]
+	fn answers_are_fixed_and_spread_out() {
+		let answers = answers(5);
+		let first = Puzzle::numbered(1, NaiveDate::MAX).unwrap();
+		assert_eq!(first.answer(&answers), first.answer(&answers));
+
+		let words: Vec<&str> = (1..=answers.len() as u32)
+			.map(|number| {
+				Puzzle::numbered(number, NaiveDate::MAX)
+					.unwrap()
+					.answer(&answers)
+			})
+			.collect();
+		let mut unique = words.clone();
+		unique.sort();
+		unique.dedup();
+		assert_eq!(
+			unique.len(),
+			answers.len(),
+			"no word repeats within a cycle"
+		);
+		assert_ne!(words[..3], answers[..3], "the order isn't alphabetical");
+	}
+}
@@ -0,0 +1,111 @@
+/// How a guessed letter compares to the word, ordered from least to most known so the best of
+/// several guesses wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LetterResult {
+	/// Not in the word, or already matched by the word's other copies of it.
+	Absent,
+	/// In the word, somewhere else.
+	Present,
+	Correct,
+}
+
+impl LetterResult {
+	pub fn ansi(&self) -> &'static str {
+		match self {
+			| LetterResult::Correct => "\x1b[32m",
+			| LetterResult::Present => "\x1b[33m",
+			| LetterResult::Absent => "\x1b[90m",
+		}
+	}
+}
+
+/// Ways a scored guess can be shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+	/// The letters in terminal colors.
+	Ansi,
+	/// Brackets for correct letters and parentheses for present ones, e.g. `[a](p) l  e  d`.
+	Plain,
+}
+
+/// Scores `guess` against `word`. Exact matches are found first, then each letter of the word
+/// that is left makes one other copy of it in the guess present, from left to right.
+pub fn score(
+	word: &str,
+	guess: &str,
+) -> Vec<LetterResult> {
+	let word: Vec<char> = word.chars().collect();
+	let guess: Vec<char> = guess.chars().collect();
+	let mut results = vec![LetterResult::Absent; guess.len()];
+	let mut remaining: Vec<Option<char>> = word.iter().copied().map(Some).collect();
+
+	for (i, c) in guess.iter().enumerate() {
+		if word.get(i) == Some(c) {
+			results[i] = LetterResult::Correct;
+			remaining[i] = None;
+		}
+	}
+
+	for (i, c) in guess.iter().enumerate() {
+		if results[i] == LetterResult::Correct {
+			continue;
+		}
+		if let Some(pos) = remaining.iter().position(|left| *left == Some(*c)) {
+			results[i] = LetterResult::Present;
+			remaining[pos] = None;
+		}
+	}
+
+	results
+}
+
+pub fn render(
+	guess: &str,
+	results: &[LetterResult],
+	format: Format,
+) -> String {
+	let letters = guess.chars().zip(results);
+	match format {
+		| Format::Ansi => letters
+			.map(|(c, result)| format!("{}{}\x1b[0m", result.ansi(), c))
+			.collect(),
+		| Format::Plain => letters
+			.map(|(c, result)| match result {
+				| LetterResult::Correct => format!("[{}]", c),
+				| LetterResult::Present => format!("({})", c),
+				| LetterResult::Absent => format!(" {} ", c),
+			})
+			.collect(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use LetterResult::*;
+
+	use super::*;
+
+	#[test]
+	fn repeated_letters_are_matched_once() {
+		// the word has one l, which the correct one takes
+		assert_eq!(
+			score("apple", "lolly"),
+			vec![Absent, Absent, Absent, Correct, Absent]
+		);
+		// one of the word's p's is guessed right and the other moved, so a third p is absent
+		assert_eq!(
+			score("apple", "ppape"),
+			vec![Present, Correct, Present, Absent, Correct]
+		);
+	}
+
+	#[test]
+	fn renders_each_format() {
+		let results = score("apple", "paled");
+		assert_eq!(
+			render("paled", &results, Format::Ansi),
+			"\x1b[33mp\x1b[0m\x1b[33ma\x1b[0m\x1b[33ml\x1b[0m\x1b[33me\x1b[0m\x1b[90md\x1b[0m"
+		);
+		assert_eq!(render("paled", &results, Format::Plain), "(p)(a)(l)(e) d ");
+	}
+}
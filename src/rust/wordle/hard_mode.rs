@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use super::Row;
+use super::feedback::LetterResult;
+
+/// `1st`, `2nd`, `3rd`, `4th` and so on.
+fn ordinal(n: usize) -> String {
+	let suffix = match (n % 10, n % 100) {
+		| (_, 11..=13) => "th",
+		| (1, _) => "st",
+		| (2, _) => "nd",
+		| (3, _) => "rd",
+		| _ => "th",
+	};
+	format!("{}{}", n, suffix)
+}
+
+/// Checks that `guess` uses every hint of the rows before it: correct letters stay where they
+/// are, and each present letter appears somewhere, as many times as the row showed it.
+pub fn check(
+	rows: &[Row],
+	guess: &str,
+) -> Result<(), String> {
+	let letters: Vec<char> = guess.chars().collect();
+
+	for (previous, results) in rows {
+		for (i, (c, result)) in previous.chars().zip(results).enumerate() {
+			if *result == LetterResult::Correct && letters.get(i) != Some(&c) {
+				return Err(format!(
+					"\x1b[31m{} letter must be \x1b[34m{}\x1b[31m.\x1b[0m",
+					ordinal(i + 1),
+					c.to_ascii_uppercase()
+				));
+			}
+		}
+	}
+
+	for (previous, results) in rows {
+		let mut required: HashMap<char, usize> = HashMap::new();
+		for (c, result) in previous.chars().zip(results) {
+			if *result != LetterResult::Absent {
+				*required.entry(c).or_default() += 1;
+			}
+		}
+
+		// in the order they were guessed, so the message is the same every time
+		for c in previous.chars() {
+			let count = required.get(&c).copied().unwrap_or(0);
+			if letters.iter().filter(|letter| **letter == c).count() < count {
+				let times = if count > 1 {
+					format!(" {} times", count)
+				} else {
+					String::new()
+				};
+				return Err(format!(
+					"\x1b[31mGuess must contain \x1b[34m{}\x1b[31m{}.\x1b[0m",
+					c.to_ascii_uppercase(),
+					times
+				));
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::wordle::rows;
+
+	#[test]
+	fn keeps_correct_letters_in_place() {
+		// l is correct in the 4th place, a is present
+		let rows = rows("apple", &["world"]);
+		assert_eq!(
+			check(&rows, "plane"),
+			Err("\x1b[31m4th letter must be \x1b[34mL\x1b[31m.\x1b[0m".to_string())
+		);
+		assert_eq!(check(&rows, "smile"), Ok(()));
+		assert_eq!(ordinal(2), "2nd");
+		assert_eq!(ordinal(12), "12th");
+	}
+
+	#[test]
+	fn uses_present_letters() {
+		let rows = rows("apple", &["paled"]);
+		assert_eq!(
+			check(&rows, "plead"),
+			Ok(()),
+			"moving present letters around is allowed"
+		);
+		assert_eq!(
+			check(&rows, "plied"),
+			Err("\x1b[31mGuess must contain \x1b[34mA\x1b[31m.\x1b[0m".to_string())
+		);
+
+		// both p's were shown, so both have to be used again
+		let rows = self::rows("apple", &["hippo"]);
+		assert_eq!(
+			check(&rows, "super"),
+			Err("\x1b[31mGuess must contain \x1b[34mP\x1b[31m 2 times.\x1b[0m".to_string())
+		);
+	}
+}
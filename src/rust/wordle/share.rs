@@ -0,0 +1,92 @@
+use super::feedback::LetterResult;
+use super::{Row, solved};
+
+/// Squares a shared result is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Squares {
+	/// 🟩 correct, 🟨 present.
+	Standard,
+	/// 🟧 correct, 🟦 present, for players who can't tell green from yellow.
+	HighContrast,
+}
+
+impl Squares {
+	fn square(
+		&self,
+		result: LetterResult,
+	) -> &'static str {
+		match (self, result) {
+			| (Squares::Standard, LetterResult::Correct) => "🟩",
+			| (Squares::Standard, LetterResult::Present) => "🟨",
+			| (Squares::HighContrast, LetterResult::Correct) => "🟧",
+			| (Squares::HighContrast, LetterResult::Present) => "🟦",
+			| (_, LetterResult::Absent) => "⬛",
+		}
+	}
+}
+
+/// A finished round as it is shared, e.g. `Wordle 123 4/6*` above a row of squares per guess,
+/// which shows how it went without giving the word away. `X` replaces the guess count of a lost
+/// round, `*` marks hard mode and free play rounds have no puzzle number.
+pub fn share_text(
+	puzzle: Option<u32>,
+	rows: &[Row],
+	max_attempts: u16,
+	hard_mode: bool,
+	squares: Squares,
+) -> String {
+	let score = if solved(rows) {
+		rows.len().to_string()
+	} else {
+		"X".to_string()
+	};
+
+	let mut header = "Wordle".to_string();
+	if let Some(number) = puzzle {
+		header.push_str(&format!(" {}", number));
+	}
+	header.push_str(&format!(" {}/{}", score, max_attempts));
+	if hard_mode {
+		header.push('*');
+	}
+
+	let grid: Vec<String> = rows
+		.iter()
+		.map(|(_, results)| {
+			results
+				.iter()
+				.map(|result| squares.square(*result))
+				.collect()
+		})
+		.collect();
+
+	format!("{}\n\n{}", header, grid.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::wordle::rows;
+
+	#[test]
+	fn shares_header_and_squares() {
+		let rows = rows("apple", &["paled", "apple"]);
+		assert_eq!(
+			share_text(Some(123), &rows, 6, false, Squares::Standard),
+			"Wordle 123 2/6\n\n🟨🟨🟨🟨⬛\n🟩🟩🟩🟩🟩"
+		);
+		assert_eq!(
+			share_text(None, &rows, 6, true, Squares::HighContrast),
+			"Wordle 2/6*\n\n🟦🟦🟦🟦⬛\n🟧🟧🟧🟧🟧"
+		);
+	}
+
+	#[test]
+	fn lost_rounds_score_x() {
+		let rows = rows("apple", &["paled", "dirty"]);
+		assert_eq!(
+			share_text(Some(7), &rows, 2, false, Squares::Standard),
+			"Wordle 7 X/2\n\n🟨🟨🟨🟨⬛\n⬛⬛⬛⬛⬛"
+		);
+	}
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use super::Row;
+use super::feedback::{LetterResult, score};
+
+/// The best first guess for 5 letter words, which takes by far the longest to work out since
+/// every answer is still possible, so it was worked out ahead of time with `best_guess`.
+pub const OPENER: &str = "raise";
+
+/// The words that would have given every row its results.
+pub fn candidates<'a>(
+	rows: &[Row],
+	words: &[&'a str],
+) -> Vec<&'a str> {
+	words
+		.iter()
+		.copied()
+		.filter(|word| {
+			rows.iter()
+				.all(|(guess, results)| score(word, guess) == *results)
+		})
+		.collect()
+}
+
+/// The expected information of `guess` in bits: how evenly its possible results split
+/// `candidates` up.
+pub fn entropy(
+	guess: &str,
+	candidates: &[&str],
+) -> f64 {
+	let mut groups: HashMap<Vec<LetterResult>, usize> = HashMap::new();
+	for word in candidates {
+		*groups.entry(score(word, guess)).or_default() += 1;
+	}
+
+	let total = candidates.len() as f64;
+	groups
+		.values()
+		.map(|count| {
+			let p = *count as f64 / total;
+			-p * p.log2()
+		})
+		.sum()
+}
+
+/// The guess out of `guesses` that is expected to tell the most about which of `candidates` is
+/// the word. Candidates win ties, since they might be the word themselves.
+pub fn best_guess<'a>(
+	candidates: &[&'a str],
+	guesses: &[&'a str],
+) -> Option<&'a str> {
+	// with two words left, guessing either is at least as good as anything else
+	if candidates.len() <= 2 {
+		return candidates.first().copied();
+	}
+
+	let mut best: Option<(&str, f64, bool)> = None;
+	for guess in guesses {
+		let bits = entropy(guess, candidates);
+		let is_candidate = candidates.contains(guess);
+		let better = match best {
+			| None => true,
+			| Some((_, best_bits, best_is_candidate)) => {
+				bits > best_bits + f64::EPSILON
+					|| ((bits - best_bits).abs() <= f64::EPSILON
+						&& is_candidate && !best_is_candidate)
+			},
+		};
+		if better {
+			best = Some((guess, bits, is_candidate));
+		}
+	}
+	best.map(|(guess, ..)| guess)
+}
+
+/// Parses the colors another client showed, `g` for green, `y` for yellow and `b`, `x`, `.` or
+/// `-` for gray, e.g. `gy..b`.
+pub fn parse_colors(
+	colors: &str,
+	length: usize,
+) -> Result<Vec<LetterResult>, String> {
+	let results = colors
+		.trim()
+		.chars()
+		.map(|c| match c.to_ascii_lowercase() {
+			| 'g' => Ok(LetterResult::Correct),
+			| 'y' => Ok(LetterResult::Present),
+			| 'b' | 'x' | '.' | '-' => Ok(LetterResult::Absent),
+			| _ => Err(format!("\x1b[34m{}\x1b[31m is not a color.\x1b[0m", c)),
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	if results.len() != length {
+		return Err(format!(
+			"\x1b[31mColors must be \x1b[34m{}\x1b[31m letters long.\x1b[0m",
+			length
+		));
+	}
+	Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+	use LetterResult::*;
+
+	use super::*;
+
+	#[test]
+	fn keeps_words_that_fit_every_row() {
+		let words = ["apple", "ample", "angle", "maple"];
+		let rows: Vec<Row> = vec![("paled".to_string(), score("apple", "paled"))];
+		assert_eq!(candidates(&rows, &words), vec!["apple", "ample"]);
+		assert_eq!(candidates(&[], &words), words);
+	}
+
+	#[test]
+	fn recommends_the_guess_that_splits_the_most() {
+		let candidates = ["bills", "fills", "hills", "mills"];
+		// guessing a candidate tells at most one word apart from the rest
+		assert!((entropy("bills", &candidates) - 0.811).abs() < 0.001);
+		assert_eq!(entropy("fbhmx", &candidates), 2.0);
+		assert_eq!(
+			best_guess(&candidates, &["bills", "fbhmx", "hills"]),
+			Some("fbhmx")
+		);
+		assert_eq!(best_guess(&candidates, &["hills", "bills"]), Some("hills"));
+		assert_eq!(best_guess(&["bills", "fills"], &["fbhmx"]), Some("bills"));
+		assert_eq!(best_guess(&[], &["fbhmx"]), None);
+	}
+
+	#[test]
+	fn parses_colors() {
+		assert_eq!(
+			parse_colors("Gy.-b", 5),
+			Ok(vec![Correct, Present, Absent, Absent, Absent])
+		);
+		assert_eq!(
+			parse_colors("gyz", 3),
+			Err("\x1b[34mz\x1b[31m is not a color.\x1b[0m".to_string())
+		);
+		assert!(parse_colors("gy", 5).is_err());
+	}
+}
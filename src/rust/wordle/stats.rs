@@ -0,0 +1,232 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// How wide the longest bar of the guess distribution is drawn.
+const CHART_WIDTH: usize = 30;
+
+/// Results of the rounds played in one mode.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+	pub played: u32,
+	pub won: u32,
+	pub current_streak: u32,
+	pub max_streak: u32,
+	/// How many rounds were won in 1 guess, in 2 guesses and so on.
+	pub distribution: Vec<u32>,
+}
+
+/// Everything saved between runs. Daily puzzles keep their own stats so free play doesn't break
+/// their streaks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsFile {
+	pub free_play:  Stats,
+	pub daily:      Stats,
+	/// The newest daily puzzle recorded, which the next one has to follow for the streak to go on.
+	pub last_daily: Option<u32>,
+}
+
+impl Stats {
+	/// Records a round, won in `guesses` or lost when there are none.
+	pub fn record(
+		&mut self,
+		guesses: Option<u16>,
+	) {
+		self.played += 1;
+		match guesses {
+			| Some(guesses) => {
+				self.won += 1;
+				self.current_streak += 1;
+				self.max_streak = self.max_streak.max(self.current_streak);
+
+				let index = guesses as usize - 1;
+				if self.distribution.len() <= index {
+					self.distribution.resize(index + 1, 0);
+				}
+				self.distribution[index] += 1;
+			},
+			| None => self.current_streak = 0,
+		}
+	}
+
+	pub fn win_percentage(&self) -> u32 {
+		if self.played == 0 {
+			return 0;
+		}
+		(self.won as f32 / self.played as f32 * 100.0).round() as u32
+	}
+
+	/// The totals, then a bar for each guess count up to `max_attempts`, with the one this round
+	/// was won in highlighted.
+	pub fn chart(
+		&self,
+		max_attempts: u16,
+		highlight: Option<u16>,
+	) -> String {
+		let mut lines = vec![
+			format!(
+				"Played {}  Win % {}  Current streak {}  Max streak {}",
+				self.played,
+				self.win_percentage(),
+				self.current_streak,
+				self.max_streak
+			),
+			String::new(),
+			"Guess distribution".to_string(),
+		];
+
+		let most = self.distribution.iter().copied().max().unwrap_or(0).max(1);
+		let rows = self.distribution.len().max(max_attempts as usize);
+		let label_width = rows.to_string().len();
+		for i in 0..rows {
+			let count = self.distribution.get(i).copied().unwrap_or(0);
+			// every win shows at least a sliver of a bar
+			let width = (count as usize * CHART_WIDTH).div_ceil(most as usize);
+			let color = if highlight == Some(i as u16 + 1) {
+				"\x1b[32m"
+			} else {
+				"\x1b[90m"
+			};
+			lines.push(format!(
+				"{:>label_width$} {}{}\x1b[0m {}",
+				i + 1,
+				color,
+				"█".repeat(width),
+				count
+			));
+		}
+
+		lines.join("\n")
+	}
+}
+
+impl StatsFile {
+	/// Where stats are kept, in the home directory if there is one.
+	pub fn path() -> PathBuf {
+		std::env::var_os("HOME")
+			.or_else(|| std::env::var_os("USERPROFILE"))
+			.map(PathBuf::from)
+			.unwrap_or_default()
+			.join(".random-things-wordle.json")
+	}
+
+	/// Loads the stats, which start out empty before the first round is saved.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		match fs::read_to_string(path) {
+			| Ok(json) => serde_json::from_str(&json)
+				.map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+			| Err(e) if e.kind() == ErrorKind::NotFound => Ok(StatsFile::default()),
+			| Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+		}
+	}
+
+	pub fn save(
+		&self,
+		path: &Path,
+	) -> Result<(), String> {
+		let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+		fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+	}
+
+	/// Records daily puzzle `number`, returning whether it counted. Only the first round of
+	/// `today`'s puzzle counts, so replays of past or future puzzles can't pad the stats or move
+	/// the streak along, and skipping a day ends the streak.
+	pub fn record_daily(
+		&mut self,
+		number: u32,
+		today: u32,
+		guesses: Option<u16>,
+	) -> bool {
+		if number != today || self.last_daily.is_some_and(|last| number <= last) {
+			return false;
+		}
+		if self.last_daily != Some(number - 1) {
+			self.daily.current_streak = 0;
+		}
+		self.daily.record(guesses);
+		self.last_daily = Some(number);
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::stuff::TempPath;
+
+	#[test]
+	fn records_streaks_and_distribution() {
+		let mut stats = Stats::default();
+		stats.record(Some(3));
+		stats.record(Some(3));
+		stats.record(Some(1));
+		stats.record(None);
+		stats.record(Some(4));
+
+		assert_eq!(stats.played, 5);
+		assert_eq!(stats.win_percentage(), 80);
+		assert_eq!(stats.current_streak, 1);
+		assert_eq!(stats.max_streak, 3);
+		assert_eq!(stats.distribution, vec![1, 0, 2, 1]);
+
+		let chart = stats.chart(6, Some(4));
+		let bars: Vec<&str> = chart.lines().skip(3).collect();
+		assert_eq!(bars.len(), 6);
+		assert_eq!(bars[2], format!("3 \x1b[90m{}\x1b[0m 2", "█".repeat(30)));
+		assert_eq!(bars[3], format!("4 \x1b[32m{}\x1b[0m 1", "█".repeat(15)));
+		assert_eq!(bars[5], "6 \x1b[90m\x1b[0m 0");
+	}
+
+	#[test]
+	fn daily_streaks_need_every_day() {
+		let mut file = StatsFile::default();
+		assert!(file.record_daily(10, 10, Some(2)));
+		assert!(file.record_daily(11, 11, Some(5)));
+		assert_eq!(file.daily.current_streak, 2);
+
+		assert!(!file.record_daily(11, 11, Some(1)), "replays don't count");
+		assert!(!file.record_daily(3, 11, Some(1)));
+		assert_eq!(file.daily.played, 2);
+
+		assert!(file.record_daily(13, 13, Some(4)));
+		assert_eq!(file.daily.current_streak, 1);
+		assert_eq!(file.daily.max_streak, 2);
+		assert_eq!(file.free_play, Stats::default());
+	}
+
+	#[test]
+	fn future_puzzles_dont_count() {
+		let mut file = StatsFile::default();
+		assert!(file.record_daily(5, 5, Some(3)));
+		assert!(
+			!file.record_daily(7, 6, Some(1)),
+			"tomorrow's puzzle played early"
+		);
+		assert_eq!(file.last_daily, Some(5));
+
+		assert!(file.record_daily(6, 6, Some(2)));
+		assert_eq!(file.last_daily, Some(6));
+		assert_eq!(file.daily.played, 2);
+		assert_eq!(file.daily.current_streak, 2);
+	}
+
+	#[test]
+	fn saves_and_loads() {
+		let path = TempPath::new("saves_and_loads.json");
+		let path = path.0.as_path();
+		assert_eq!(StatsFile::load(path), Ok(StatsFile::default()));
+
+		let mut file = StatsFile::default();
+		file.free_play.record(Some(2));
+		file.record_daily(1, 1, None);
+		file.save(path).unwrap();
+		assert_eq!(StatsFile::load(path), Ok(file));
+
+		fs::write(path, "not json").unwrap();
+		assert!(StatsFile::load(path).is_err());
+	}
+}